@@ -0,0 +1,320 @@
+//! Timings of deserialization, run with `cargo bench`.
+//!
+//! Each benchmark compares the crate's implementation with a naive one, that is implemented here
+//! as a [`TsNode`] wrapper.
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use serde_tree_sitter::{from_ts_node, TsNode, TsNodeImpl};
+
+const RUNS: usize = 20;
+
+fn best_of<T>(mut f: impl FnMut() -> T) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            std::hint::black_box(f());
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn parse(src: &str) -> tree_sitter::Tree {
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(tree_sitter_json::language()).unwrap();
+    parser.parse(src, None).unwrap()
+}
+
+/// [`TsNodeImpl`] that collects children into a `Vec` before iterating them, as it did before
+/// iterating with a cursor.
+#[derive(Clone, Debug)]
+struct Collected<'a, 'de>(TsNodeImpl<'a, 'de>);
+
+impl<'a, 'de> TsNode<'de> for Collected<'a, 'de> {
+    fn named_child(&self, index: usize) -> Option<Self> {
+        self.0.named_child(index).map(Collected)
+    }
+    fn named_child_count(&self) -> usize {
+        self.0.named_child_count()
+    }
+    fn named_children(&self) -> impl Iterator<Item = Self> {
+        self.0
+            .named_children()
+            .map(Collected)
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+    fn child_count(&self) -> usize {
+        self.0.child_count()
+    }
+    fn children(&self) -> impl Iterator<Item = Self> {
+        self.0
+            .children()
+            .map(Collected)
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+    fn children_by_field_name(&self, name: &str) -> impl Iterator<Item = Self> {
+        self.0.children_by_field_name(name).map(Collected)
+    }
+    fn field_names(&self) -> Vec<&'static str> {
+        self.0.field_names()
+    }
+    fn field_children(&self) -> Vec<(&'static str, Self)> {
+        let children = self.0.field_children().into_iter();
+        children.map(|(f, c)| (f, Collected(c))).collect()
+    }
+    fn kind(&self) -> &'static str {
+        self.0.kind()
+    }
+    fn src(&self) -> &'de str {
+        self.0.src()
+    }
+    fn byte_range(&self) -> std::ops::Range<usize> {
+        self.0.byte_range()
+    }
+    fn start_position(&self) -> tree_sitter::Point {
+        self.0.start_position()
+    }
+    fn end_position(&self) -> tree_sitter::Point {
+        self.0.end_position()
+    }
+    fn id(&self) -> usize {
+        self.0.id()
+    }
+    fn is_error(&self) -> bool {
+        self.0.is_error()
+    }
+}
+
+// Values are built but not read.
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Value {
+    Array(Vec<Value>),
+    Number(u32),
+}
+
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+#[serde(rename = "document")]
+struct Document(Value);
+
+/// Arrays nested `depth` levels, each with `width` numbers besides the nested array.
+fn nested_arrays(depth: usize, width: usize) -> String {
+    let numbers = vec!["1"; width].join(", ");
+    let mut src = String::new();
+    for _ in 0..depth {
+        src.push('[');
+        src.push_str(&numbers);
+        src.push_str(", ");
+    }
+    src.push_str("[]");
+    src.push_str(&"]".repeat(depth));
+    src
+}
+
+/// Deserializing deeply nested nodes, iterating children with a cursor vs. collecting them.
+fn bench_nested() {
+    let src = nested_arrays(1000, 16);
+    let tree = parse(&src);
+    let root = tree.root_node();
+
+    let cursor = best_of(|| from_ts_node::<Document, _>(TsNodeImpl::new(root, &src)).unwrap());
+    let collected =
+        best_of(|| from_ts_node::<Document, _>(Collected(TsNodeImpl::new(root, &src))).unwrap());
+    println!("nested arrays(depth 1000, width 16)");
+    println!("  iterate with cursor: {cursor:?}");
+    println!("  collect into Vec:    {collected:?}");
+}
+
+/// In-memory node that counts scans of its children. A tree-sitter node allocates a cursor for
+/// each scan.
+#[derive(Clone, Debug)]
+struct CountingNode {
+    kind: &'static str,
+    src: &'static str,
+    id: usize,
+    children: Rc<Vec<(&'static str, CountingNode)>>,
+    scans: Rc<Cell<usize>>,
+}
+
+impl CountingNode {
+    fn scan(&self) -> impl Iterator<Item = &(&'static str, CountingNode)> {
+        self.scans.set(self.scans.get() + 1);
+        self.children.iter()
+    }
+}
+
+impl<'de> TsNode<'de> for CountingNode {
+    fn named_child(&self, index: usize) -> Option<Self> {
+        self.children.get(index).map(|(_, c)| c.clone())
+    }
+    fn named_child_count(&self) -> usize {
+        self.children.len()
+    }
+    fn named_children(&self) -> impl Iterator<Item = Self> {
+        self.scan().map(|(_, c)| c.clone())
+    }
+    fn child_count(&self) -> usize {
+        self.children.len()
+    }
+    fn children(&self) -> impl Iterator<Item = Self> {
+        self.named_children()
+    }
+    fn children_by_field_name(&self, name: &str) -> impl Iterator<Item = Self> {
+        let children = self.scan().filter(|(f, _)| *f == name);
+        children
+            .map(|(_, c)| c.clone())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+    fn field_names(&self) -> Vec<&'static str> {
+        self.scan().map(|(f, _)| *f).collect()
+    }
+    fn field_children(&self) -> Vec<(&'static str, Self)> {
+        self.scan().cloned().collect()
+    }
+    fn kind(&self) -> &'static str {
+        self.kind
+    }
+    fn src(&self) -> &'de str {
+        self.src
+    }
+    fn byte_range(&self) -> std::ops::Range<usize> {
+        0..self.src.len()
+    }
+    fn start_position(&self) -> tree_sitter::Point {
+        tree_sitter::Point::new(0, 0)
+    }
+    fn end_position(&self) -> tree_sitter::Point {
+        tree_sitter::Point::new(0, self.src.len())
+    }
+    fn id(&self) -> usize {
+        self.id
+    }
+    fn is_error(&self) -> bool {
+        false
+    }
+}
+
+/// [`CountingNode`] that takes the field children with the default implementation, which calls
+/// `children_by_field_name` for each field as the deserializer did before `field_children`.
+#[derive(Clone, Debug)]
+struct PerField(CountingNode);
+
+impl<'de> TsNode<'de> for PerField {
+    fn named_child(&self, index: usize) -> Option<Self> {
+        self.0.named_child(index).map(PerField)
+    }
+    fn named_child_count(&self) -> usize {
+        self.0.named_child_count()
+    }
+    fn named_children(&self) -> impl Iterator<Item = Self> {
+        TsNode::named_children(&self.0).map(PerField)
+    }
+    fn child_count(&self) -> usize {
+        self.0.child_count()
+    }
+    fn children(&self) -> impl Iterator<Item = Self> {
+        TsNode::children(&self.0).map(PerField)
+    }
+    fn children_by_field_name(&self, name: &str) -> impl Iterator<Item = Self> {
+        TsNode::children_by_field_name(&self.0, name).map(PerField)
+    }
+    fn field_names(&self) -> Vec<&'static str> {
+        TsNode::field_names(&self.0)
+    }
+    fn kind(&self) -> &'static str {
+        self.0.kind
+    }
+    fn src(&self) -> &'de str {
+        self.0.src
+    }
+    fn byte_range(&self) -> std::ops::Range<usize> {
+        TsNode::byte_range(&self.0)
+    }
+    fn start_position(&self) -> tree_sitter::Point {
+        TsNode::start_position(&self.0)
+    }
+    fn end_position(&self) -> tree_sitter::Point {
+        TsNode::end_position(&self.0)
+    }
+    fn id(&self) -> usize {
+        self.0.id
+    }
+    fn is_error(&self) -> bool {
+        false
+    }
+}
+
+macro_rules! wide_struct {
+    ($($field:ident)*) => {
+        #[allow(dead_code)]
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "wide")]
+        struct Wide {
+            $($field: u32,)*
+        }
+
+        const WIDE_FIELDS: &[&str] = &[$(stringify!($field)),*];
+    };
+}
+
+wide_struct!(
+    f00 f01 f02 f03 f04 f05 f06 f07 f08 f09 f10 f11 f12 f13 f14 f15 f16 f17 f18 f19 f20 f21 f22
+    f23 f24 f25 f26 f27 f28 f29 f30 f31 f32 f33 f34 f35 f36 f37 f38 f39 f40 f41 f42 f43 f44 f45
+    f46 f47 f48 f49
+);
+
+/// Deserializing a struct with 50 fields, collecting the field children in one scan vs. scanning
+/// the children for each field.
+fn bench_wide() {
+    let scans = Rc::new(Cell::new(0));
+    let leaf = |id| CountingNode {
+        kind: "number",
+        src: "1",
+        id,
+        children: Rc::new(vec![]),
+        scans: scans.clone(),
+    };
+    let node = CountingNode {
+        kind: "wide",
+        src: "",
+        id: 0,
+        children: Rc::new(
+            WIDE_FIELDS
+                .iter()
+                .enumerate()
+                .map(|(i, f)| (*f, leaf(i + 1)))
+                .collect(),
+        ),
+        scans: scans.clone(),
+    };
+
+    let count_scans = |f: &dyn Fn()| {
+        scans.set(0);
+        f();
+        scans.get()
+    };
+    let one_pass = count_scans(&|| {
+        from_ts_node::<Wide, _>(node.clone()).unwrap();
+    });
+    let per_field = count_scans(&|| {
+        from_ts_node::<Wide, _>(PerField(node.clone())).unwrap();
+    });
+    let one_pass_time = best_of(|| from_ts_node::<Wide, _>(node.clone()).unwrap());
+    let per_field_time = best_of(|| from_ts_node::<Wide, _>(PerField(node.clone())).unwrap());
+    println!("struct with {} fields", WIDE_FIELDS.len());
+    println!("  field children in one pass: {one_pass} scans, {one_pass_time:?}");
+    println!("  children for each field:    {per_field} scans, {per_field_time:?}");
+}
+
+fn main() {
+    bench_nested();
+    bench_wide();
+}
@@ -0,0 +1,91 @@
+//! A `deserialize_with` function that needs both the text and the children of a node.
+//!
+//! The deserializer given to the function can be consumed only once. To get the text and the
+//! children together, deserialize an intermediate struct that captures the node's text with
+//! `$text`, its span with `$span` and its children with `$rest`, then post-process it.
+
+use std::io::Read;
+
+use serde::{Deserialize, Deserializer};
+use serde_tree_sitter::{Span, Spanned};
+
+#[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename = "document")]
+struct Document(Vec<Value>);
+
+#[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Value {
+    Object(Vec<Pair>),
+    Number(String),
+    Array(Vec<Value>),
+    String(#[serde(deserialize_with = "unescape")] String),
+    Null,
+}
+
+#[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename = "pair")]
+struct Pair {
+    key: StringContainer,
+    value: Box<Value>,
+}
+
+#[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename = "string")]
+struct StringContainer(#[serde(deserialize_with = "unescape")] String);
+
+/// The text of a `string_content` node, and its `escape_sequence` children with their ranges.
+#[derive(serde::Deserialize)]
+#[serde(rename = "string_content")]
+struct RawStringContent {
+    #[serde(rename = "$span")]
+    span: Span,
+    #[serde(rename = "$text")]
+    text: String,
+    #[serde(rename = "$rest")]
+    escapes: Vec<Spanned<String>>,
+}
+
+/// Unescapes the `string_content` child of a `string` node, if any.
+fn unescape<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let Some(raw) = Option::<RawStringContent>::deserialize(deserializer)? else {
+        return Ok(String::new());
+    };
+    let mut out = String::new();
+    let mut pos = 0;
+    for escape in raw.escapes {
+        let start = escape.range.start - raw.span.start;
+        out.push_str(&raw.text[pos..start]);
+        out.push(decode_escape(&escape.value).ok_or_else(|| {
+            serde::de::Error::custom(format!("Invalid escape sequence: {}", escape.value))
+        })?);
+        pos = escape.range.end - raw.span.start;
+    }
+    out.push_str(&raw.text[pos..]);
+    Ok(out)
+}
+
+fn decode_escape(escape: &str) -> Option<char> {
+    match escape.strip_prefix('\\')? {
+        "b" => Some('\u{0008}'),
+        "n" => Some('\n'),
+        "r" => Some('\r'),
+        "t" => Some('\t'),
+        "f" => Some('\u{000c}'),
+        s if s.starts_with('u') => char::from_u32(u32::from_str_radix(&s[1..], 16).ok()?),
+        s => s.chars().next(),
+    }
+}
+
+fn main() {
+    let json_language = tree_sitter_json::language();
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(json_language).unwrap();
+
+    let mut buf = String::new();
+    std::io::stdin().read_to_string(&mut buf).unwrap();
+
+    let tree = parser.parse(&buf, None).unwrap();
+
+    let _ = dbg!(serde_tree_sitter::from_tree::<Document>(&tree, &buf));
+}
@@ -0,0 +1,113 @@
+//! Intern the strings of a JSON array while deserializing it, with a `DeserializeSeed` that holds
+//! the interner.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Read;
+
+use serde::de::{DeserializeSeed, Deserializer, SeqAccess, Visitor};
+
+#[derive(Debug, Default)]
+struct Interner {
+    symbols: HashMap<String, usize>,
+    strings: Vec<String>,
+}
+
+impl Interner {
+    fn intern(&mut self, s: &str) -> Symbol {
+        if let Some(&id) = self.symbols.get(s) {
+            return Symbol(id);
+        }
+        let id = self.strings.len();
+        self.symbols.insert(s.to_owned(), id);
+        self.strings.push(s.to_owned());
+        Symbol(id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Symbol(usize);
+
+/// Deserializes a `document` node that contains an `array` of strings.
+struct DocumentSeed<'a>(&'a mut Interner);
+
+impl<'de, 'a> DeserializeSeed<'de> for DocumentSeed<'a> {
+    type Value = Vec<Symbol>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_newtype_struct("document", self)
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for DocumentSeed<'a> {
+    type Value = Vec<Symbol>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a document")
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_newtype_struct("array", ArrayVisitor(self.0))
+    }
+}
+
+struct ArrayVisitor<'a>(&'a mut Interner);
+
+impl<'de, 'a> Visitor<'de> for ArrayVisitor<'a> {
+    type Value = Vec<Symbol>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of strings")
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut symbols = Vec::new();
+        while let Some(symbol) = seq.next_element_seed(SymbolSeed(self.0))? {
+            symbols.push(symbol);
+        }
+        Ok(symbols)
+    }
+}
+
+/// Deserializes a `string` node into a symbol.
+struct SymbolSeed<'a>(&'a mut Interner);
+
+impl<'de, 'a> DeserializeSeed<'de> for SymbolSeed<'a> {
+    type Value = Symbol;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let text = <&str as serde::Deserialize>::deserialize(deserializer)?;
+        Ok(self.0.intern(text.trim_matches('"')))
+    }
+}
+
+fn main() {
+    let json_language = tree_sitter_json::language();
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(json_language).unwrap();
+
+    let mut buf = String::new();
+    std::io::stdin().read_to_string(&mut buf).unwrap();
+
+    let tree = parser.parse(&buf, None).unwrap();
+
+    let mut interner = Interner::default();
+    let symbols = serde_tree_sitter::from_node_seed(
+        DocumentSeed(&mut interner),
+        tree.root_node(),
+        &buf,
+        true,
+    );
+    let _ = dbg!(symbols);
+    dbg!(interner.strings);
+}
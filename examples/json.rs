@@ -50,10 +50,7 @@ fn parse_string_content<'de, D: Deserializer<'de>>(deserializer: D) -> Result<St
     let raw = String::deserialize(deserializer)?;
     let mut out = String::new();
     let mut it = raw.chars();
-    loop {
-        let Some(c) = it.next() else {
-            break;
-        };
+    while let Some(c) = it.next() {
         if c == '\\' {
             let c2 = next_char(&mut it, &raw)?;
             let c2 = match c2 {
@@ -91,5 +88,5 @@ fn main() {
 
     let tree = parser.parse(&buf, None).unwrap();
 
-    let _ = dbg!(serde_tree_sitter::from_tree::<Document>(&tree, &buf, true));
+    let _ = dbg!(serde_tree_sitter::from_tree::<Document>(&tree, &buf));
 }
@@ -0,0 +1,77 @@
+//! Reassemble a JSON document from deserialized values, optionally rewriting some of them.
+
+use serde_tree_sitter::{reassemble, SourceSpanned, Span};
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename = "document")]
+struct Document(Vec<Object>);
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename = "object")]
+struct Object(Vec<Pair>);
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename = "pair")]
+struct Pair {
+    #[serde(rename = "$span")]
+    span: Span,
+    key: Key,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename = "string")]
+struct Key {
+    #[serde(rename = "$span")]
+    span: Span,
+}
+
+impl SourceSpanned for Pair {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl SourceSpanned for Key {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Rebuilds the source from pairs. Text between pairs is copied from the original source.
+fn rebuild(src: &str, doc: &Document, rewrite_key: impl Fn(&str) -> String) -> String {
+    let mut out = String::new();
+    let mut pos = 0;
+    for pair in doc.0.iter().flat_map(|object| object.0.iter()) {
+        out.push_str(&src[pos..pair.span().start]);
+        out.push_str(&src[pair.span().start..pair.key.span().start]);
+        out.push_str(&rewrite_key(&reassemble(src, &pair.key)));
+        out.push_str(&src[pair.key.span().end..pair.span().end]);
+        pos = pair.span().end;
+    }
+    out.push_str(&src[pos..]);
+    out
+}
+
+fn main() {
+    let src = r#"
+    {
+        "name": "serde_tree_sitter",
+        "tags" :[ "serde",  "tree-sitter" ],
+        "nested": {"a":1}
+    }
+    "#;
+
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(tree_sitter_json::language()).unwrap();
+    let tree = parser.parse(src, None).unwrap();
+
+    let doc: Document = serde_tree_sitter::from_tree(&tree, src).unwrap();
+
+    // Lossless round-trip
+    let identity = rebuild(src, &doc, |key| key.to_owned());
+    assert_eq!(identity, src);
+
+    // Codemod: upper-case all top-level keys, keeping everything else as is
+    let upper = rebuild(src, &doc, |key| key.to_uppercase());
+    println!("{upper}");
+}
@@ -1,10 +1,17 @@
 use std::marker::PhantomData;
+use std::rc::Rc;
 
-use crate::{deserializer::NodeDeserializer, tsnode::TsNode, DeserializeError};
+use crate::{
+    context::{Context, Entered},
+    deserializer::NodeDeserializer,
+    tsnode::TsNode,
+    DeserializeError,
+};
 
 pub struct SeqAccess<'de, N: TsNode<'de>, I: Iterator<Item = N>> {
     nodes: I,
-    _p: PhantomData<&'de N>,
+    ctx: Rc<Context>,
+    _p: PhantomData<&'de ()>,
 }
 
 impl<'de, N: TsNode<'de>, I: Iterator<Item = N>> serde::de::SeqAccess<'de>
@@ -19,33 +26,262 @@ impl<'de, N: TsNode<'de>, I: Iterator<Item = N>> serde::de::SeqAccess<'de>
         let Some(n) = self.nodes.next() else {
             return Ok(None);
         };
-        let v = seed.deserialize(NodeDeserializer::new(n))?;
+        self.ctx.count_element()?;
+        let v = seed.deserialize(NodeDeserializer::enter(n, self.ctx.clone())?)?;
         Ok(Some(v))
     }
+
+    fn size_hint(&self) -> Option<usize> {
+        match self.nodes.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(upper),
+            _ => None,
+        }
+    }
 }
 
 impl<'de, N: TsNode<'de>, I: Iterator<Item = N>> SeqAccess<'de, N, I> {
-    pub fn new(nodes: I) -> SeqAccess<'de, N, I> {
+    pub fn new(nodes: I, ctx: Rc<Context>) -> SeqAccess<'de, N, I> {
         SeqAccess {
             nodes,
+            ctx,
+            _p: PhantomData,
+        }
+    }
+}
+
+/// Accesses named children as elements of a tuple. The last element takes all remaining children.
+pub struct TupleAccess<'de, N: TsNode<'de>> {
+    nodes: std::vec::IntoIter<N>,
+    len: usize,
+    index: usize,
+    field_name: Option<&'static str>,
+    ctx: Rc<Context>,
+    _p: PhantomData<&'de ()>,
+}
+
+impl<'de, N: TsNode<'de>> TupleAccess<'de, N> {
+    pub fn new(nodes: Vec<N>, len: usize, ctx: Rc<Context>) -> TupleAccess<'de, N> {
+        TupleAccess {
+            nodes: nodes.into_iter(),
+            len,
+            index: 0,
+            field_name: None,
+            ctx,
+            _p: PhantomData,
+        }
+    }
+    /// Accesses the nodes of a struct field instead of named children.
+    pub fn in_field(mut self, field_name: &'static str) -> Self {
+        self.field_name = Some(field_name);
+        self
+    }
+}
+
+impl<'de, N: TsNode<'de>> serde::de::SeqAccess<'de> for TupleAccess<'de, N> {
+    type Error = crate::DeserializeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        if self.len <= self.index {
+            return Ok(None);
+        }
+        self.index += 1;
+        if self.index < self.len {
+            self.ctx.count_element()?;
+            let n = self.nodes.next().unwrap();
+            return seed
+                .deserialize(NodeDeserializer::enter(n, self.ctx.clone())?)
+                .map(Some);
+        }
+        let rest = std::mem::take(&mut self.nodes).collect::<Vec<_>>();
+        let child_count = self.len - 1 + rest.len();
+        seed.deserialize(
+            crate::deserializer::TailDeserializer::new(
+                rest,
+                self.len,
+                child_count,
+                self.ctx.clone(),
+            )
+            .in_field(self.field_name),
+        )
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.index)
+    }
+}
+
+/// Accesses presence of a value and the value itself, for [`crate::Presence`].
+pub struct PresenceAccess<D> {
+    present: Option<bool>,
+    value: Option<D>,
+}
+
+impl<D> PresenceAccess<D> {
+    pub fn new(present: bool, value: D) -> Self {
+        PresenceAccess {
+            present: Some(present),
+            value: Some(value),
+        }
+    }
+}
+
+impl<'de, D: serde::Deserializer<'de, Error = DeserializeError>> serde::de::SeqAccess<'de>
+    for PresenceAccess<D>
+{
+    type Error = DeserializeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        if let Some(present) = self.present.take() {
+            return seed
+                .deserialize(serde::de::value::BoolDeserializer::new(present))
+                .map(Some);
+        }
+        match self.value.take() {
+            Some(value) => seed.deserialize(value).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Accesses a node twice, as the two elements of a wrapper like [`crate::Located`]. Unlike
+/// [`SeqAccess`], the node is not counted as an element nor entered again.
+pub struct WrapperAccess<D> {
+    first: Option<D>,
+    second: Option<D>,
+}
+
+impl<D> WrapperAccess<D> {
+    pub fn new(first: D, second: D) -> Self {
+        WrapperAccess {
+            first: Some(first),
+            second: Some(second),
+        }
+    }
+}
+
+impl<'de, D: serde::Deserializer<'de, Error = DeserializeError>> serde::de::SeqAccess<'de>
+    for WrapperAccess<D>
+{
+    type Error = DeserializeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        match self.first.take().or_else(|| self.second.take()) {
+            Some(de) => seed.deserialize(de).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(usize::from(self.first.is_some()) + usize::from(self.second.is_some()))
+    }
+}
+
+/// Accesses the kind, the text, the field names of named children and named children of a node,
+/// for [`crate::TreeValue`]. Children not assigned to any field have empty field names.
+pub struct TreeValueAccess<'de, N: TsNode<'de>> {
+    node: N,
+    index: usize,
+    ctx: Rc<Context>,
+    _p: PhantomData<&'de ()>,
+}
+
+impl<'de, N: TsNode<'de>> TreeValueAccess<'de, N> {
+    pub fn new(node: N, ctx: Rc<Context>) -> Self {
+        TreeValueAccess {
+            node,
+            index: 0,
+            ctx,
             _p: PhantomData,
         }
     }
+
+    fn child_field_names(&self) -> Vec<&'static str> {
+        let fields = self
+            .node
+            .field_children()
+            .into_iter()
+            .map(|(f, c)| (c.id(), f))
+            .collect::<std::collections::HashMap<_, _>>();
+        self.node
+            .named_children()
+            .map(|c| fields.get(&c.id()).copied().unwrap_or(""))
+            .collect()
+    }
+}
+
+impl<'de, N: TsNode<'de>> serde::de::SeqAccess<'de> for TreeValueAccess<'de, N> {
+    type Error = DeserializeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        self.index += 1;
+        let value = match self.index {
+            1 => seed.deserialize(serde::de::value::BorrowedStrDeserializer::new(
+                self.node.kind(),
+            ))?,
+            2 => seed.deserialize(serde::de::value::BorrowedStrDeserializer::new(
+                crate::tsnode::text(&self.node)?,
+            ))?,
+            3 => seed.deserialize(serde::de::value::SeqDeserializer::new(
+                self.child_field_names().into_iter(),
+            ))?,
+            4 => seed.deserialize(NodeDeserializer::new(self.node.clone(), self.ctx.clone()))?,
+            _ => return Ok(None),
+        };
+        Ok(Some(value))
+    }
 }
 
 pub struct EnumAccess<'de, N: TsNode<'de>> {
     node: N,
     name: &'static str,
-    _p: PhantomData<&'de N>,
+    variants: &'static [&'static str],
+    ctx: Rc<Context>,
+    _p: PhantomData<&'de ()>,
 }
 impl<'de, N: TsNode<'de>> EnumAccess<'de, N> {
-    pub fn new(node: N, name: &'static str) -> EnumAccess<'de, N> {
+    pub fn new(
+        node: N,
+        name: &'static str,
+        variants: &'static [&'static str],
+        ctx: Rc<Context>,
+    ) -> EnumAccess<'de, N> {
         EnumAccess {
             node,
             name,
+            variants,
+            ctx,
             _p: PhantomData,
         }
     }
+
+    /// Variant selected by the node's kind, if it can't be left to serde's exact match: a variant
+    /// that matches ignoring case, or the `$other` variant.
+    fn resolve_variant(&self) -> Option<&'static str> {
+        let kind = self.ctx.options.normalize(self.node.kind());
+        if self.variants.contains(&kind.as_ref()) {
+            return None;
+        }
+        if self.ctx.options.is_case_insensitive_kinds() {
+            if let Some(variant) = self.variants.iter().find(|v| v.eq_ignore_ascii_case(&kind)) {
+                return Some(variant);
+            }
+        }
+        let other = crate::types::OTHER_VARIANT;
+        self.variants.contains(&other).then_some(other)
+    }
 }
 impl<'de, N: TsNode<'de>> serde::de::EnumAccess<'de> for EnumAccess<'de, N> {
     type Error = DeserializeError;
@@ -56,8 +292,34 @@ impl<'de, N: TsNode<'de>> serde::de::EnumAccess<'de> for EnumAccess<'de, N> {
     where
         V: serde::de::DeserializeSeed<'de>,
     {
-        let value = seed.deserialize(NodeDeserializer::new(self.node.clone()))?;
-        let variant_access = VariantAccess::new(self.node, self.name);
+        if let Some((variant, child)) = self
+            .ctx
+            .options
+            .variant_by_child_kind(self.name, &self.node)
+        {
+            let entered = self.ctx.enter(&child)?;
+            let value =
+                seed.deserialize(serde::de::value::BorrowedStrDeserializer::new(variant))?;
+            let mut variant_access = VariantAccess::new(child, self.name, variant, self.ctx);
+            variant_access.entered = entered;
+            return Ok((value, variant_access));
+        }
+        if let Some(variant) = self.resolve_variant() {
+            let value =
+                seed.deserialize(serde::de::value::BorrowedStrDeserializer::new(variant))?;
+            let variant_access = VariantAccess::new(self.node, self.name, variant, self.ctx);
+            return Ok((value, variant_access));
+        }
+        let value = seed.deserialize(NodeDeserializer::new(self.node.clone(), self.ctx.clone()))?;
+        // serde matched the node's kind as the identifier
+        let kind = self.ctx.options.normalize(self.node.kind());
+        let variant = self
+            .variants
+            .iter()
+            .find(|v| **v == kind)
+            .copied()
+            .unwrap_or(self.node.kind());
+        let variant_access = VariantAccess::new(self.node, self.name, variant, self.ctx);
         Ok((value, variant_access))
     }
 }
@@ -65,13 +327,24 @@ impl<'de, N: TsNode<'de>> serde::de::EnumAccess<'de> for EnumAccess<'de, N> {
 pub struct VariantAccess<'de, N: TsNode<'de>> {
     node: N,
     name: &'static str,
-    _p: PhantomData<&'de N>,
+    variant: &'static str,
+    ctx: Rc<Context>,
+    entered: Option<Entered>,
+    _p: PhantomData<&'de ()>,
 }
 impl<'de, N: TsNode<'de>> VariantAccess<'de, N> {
-    pub fn new(node: N, name: &'static str) -> VariantAccess<'de, N> {
+    pub fn new(
+        node: N,
+        name: &'static str,
+        variant: &'static str,
+        ctx: Rc<Context>,
+    ) -> VariantAccess<'de, N> {
         VariantAccess {
             node,
             name,
+            variant,
+            ctx,
+            entered: None,
             _p: PhantomData,
         }
     }
@@ -80,6 +353,7 @@ impl<'de, N: TsNode<'de>> serde::de::VariantAccess<'de> for VariantAccess<'de, N
     type Error = DeserializeError;
 
     fn unit_variant(self) -> Result<(), Self::Error> {
+        self.ctx.unconsumed(self.node.named_children());
         Ok(())
     }
 
@@ -87,8 +361,13 @@ impl<'de, N: TsNode<'de>> serde::de::VariantAccess<'de> for VariantAccess<'de, N
     where
         T: serde::de::DeserializeSeed<'de>,
     {
+        if self.variant == crate::types::OTHER_VARIANT {
+            return seed.deserialize(serde::de::value::BorrowedStrDeserializer::new(
+                self.node.kind(),
+            ));
+        }
         seed.deserialize(crate::deserializer::NewtypeStructDeserializer::new(
-            self.name, self.node,
+            self.name, self.node, self.ctx,
         ))
     }
 
@@ -97,12 +376,15 @@ impl<'de, N: TsNode<'de>> serde::de::VariantAccess<'de> for VariantAccess<'de, N
         V: serde::de::Visitor<'de>,
     {
         if self.node.named_child_count() != len {
-            return Err(DeserializeError::child_length(
+            return Err(DeserializeError::variant_child_length(
+                self.name,
+                self.node.kind(),
+                self.variant,
                 len,
                 self.node.named_child_count(),
             ));
         }
-        let seq = SeqAccess::new(self.node.named_children());
+        let seq = SeqAccess::new(self.node.named_children(), self.ctx);
         visitor.visit_seq(seq)
     }
 
@@ -114,30 +396,73 @@ impl<'de, N: TsNode<'de>> serde::de::VariantAccess<'de> for VariantAccess<'de, N
     where
         V: serde::de::Visitor<'de>,
     {
-        visitor.visit_seq(FieldsAsSeqAccess {
-            node: self.node,
-            fields,
-            index: 0,
-            _p: PhantomData,
-        })
+        FieldsAsSeqAccess::new(self.node, fields, self.ctx).visit(visitor)
     }
 }
 
+/// Accesses struct fields as elements of a sequence, in the declared order.
+///
+/// If a field has no node and its type needs one, the element is reported as missing so that
+/// serde fills in the default value(`#[serde(default)]`).
 pub struct FieldsAsSeqAccess<'de, N: TsNode<'de>> {
     node: N,
     fields: &'static [&'static str],
+    /// Children of each field, collected at once to avoid scanning the children per field.
+    children: Vec<(&'static str, Vec<N>)>,
     index: usize,
-    _p: PhantomData<&'de N>,
+    /// Error of the last element reported as missing.
+    missing: Option<DeserializeError>,
+    ctx: Rc<Context>,
+    _p: PhantomData<&'de ()>,
 }
 impl<'de, N: TsNode<'de>> FieldsAsSeqAccess<'de, N> {
-    pub fn new(node: N, fields: &'static [&'static str]) -> Self {
+    pub fn new(node: N, fields: &'static [&'static str], ctx: Rc<Context>) -> Self {
+        let mut children = group_by_field(&node);
+        let has_rest = fields.contains(&crate::types::REST_FIELD);
+        let callback = ctx.options.ignored_child_callback();
+        let track = ctx.options.is_deny_unconsumed_children();
+        if has_rest || callback.is_some() || track {
+            let claimed = children
+                .iter()
+                .filter(|(f, _)| fields.contains(f) && !crate::types::is_reserved_field(f))
+                .flat_map(|(_, nodes)| nodes.iter().map(|c| c.id()))
+                .collect::<std::collections::HashSet<_>>();
+            let unclaimed = node.named_children().filter(|c| !claimed.contains(&c.id()));
+            if has_rest {
+                // Children in the rest are not ignored, so they are not reported.
+                children.push((crate::types::REST_FIELD, unclaimed.collect()));
+            } else {
+                let unclaimed = unclaimed.collect::<Vec<_>>();
+                if let Some(callback) = callback {
+                    for child in &unclaimed {
+                        callback(child.kind(), child.byte_range());
+                    }
+                }
+                ctx.unconsumed(unclaimed);
+            }
+        }
         FieldsAsSeqAccess {
             node,
             fields,
+            children,
             index: 0,
+            missing: None,
+            ctx,
             _p: PhantomData,
         }
     }
+
+    pub fn visit<V: serde::de::Visitor<'de>>(
+        mut self,
+        visitor: V,
+    ) -> Result<V::Value, DeserializeError> {
+        let result = visitor.visit_seq(&mut self);
+        match (result, self.missing) {
+            // serde fails right after a missing element if the field has no default value.
+            (Err(_), Some(missing)) => Err(missing),
+            (result, _) => result,
+        }
+    }
 }
 impl<'de, N: TsNode<'de>> serde::de::SeqAccess<'de> for FieldsAsSeqAccess<'de, N> {
     type Error = DeserializeError;
@@ -146,16 +471,136 @@ impl<'de, N: TsNode<'de>> serde::de::SeqAccess<'de> for FieldsAsSeqAccess<'de, N
     where
         T: serde::de::DeserializeSeed<'de>,
     {
+        self.missing = None;
         if self.fields.len() <= self.index {
             return Ok(None);
         }
         let field = self.fields[self.index];
         self.index += 1;
-        let nodes = self.node.children_by_field_name(field);
-        seed.deserialize(crate::deserializer::FieldDeserializer::new(
+        if crate::types::is_reserved_field(field) {
+            return seed
+                .deserialize(NodeDeserializer::new(self.node.clone(), self.ctx.clone()))
+                .map(Some);
+        }
+        let nodes = self
+            .children
+            .iter_mut()
+            .find(|(f, _)| *f == field)
+            .map(|(_, nodes)| std::mem::take(nodes))
+            .unwrap_or_default();
+        let absent = nodes.is_empty();
+        match seed.deserialize(crate::deserializer::FieldDeserializer::new(
             field,
-            nodes.collect(),
-        ))
-        .map(Some)
+            nodes,
+            self.ctx.clone(),
+        )) {
+            Ok(value) => Ok(Some(value)),
+            Err(err @ DeserializeError::FieldLength { actual: 0, .. }) if absent => {
+                self.missing = Some(err);
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.fields.len() - self.index)
+    }
+}
+
+/// Accesses fields of the node as map entries keyed by field names.
+pub struct FieldsAsMapAccess<'de, N: TsNode<'de>> {
+    /// Entries of reserved keys, that precede the fields.
+    reserved: std::vec::IntoIter<(&'static str, &'de str)>,
+    fields: std::vec::IntoIter<(&'static str, Vec<N>)>,
+    /// Value of the current reserved entry, or the current field and its children.
+    current: Option<Result<&'de str, (&'static str, Vec<N>)>>,
+    ctx: Rc<Context>,
+    _p: PhantomData<&'de ()>,
+}
+impl<'de, N: TsNode<'de>> FieldsAsMapAccess<'de, N> {
+    pub fn new(node: N, ctx: Rc<Context>) -> Self {
+        Self::from_fields(group_by_field(&node), ctx)
+    }
+
+    /// Map over already grouped nodes, e.g. captures of a query match.
+    pub fn from_fields(fields: Vec<(&'static str, Vec<N>)>, ctx: Rc<Context>) -> Self {
+        FieldsAsMapAccess {
+            reserved: vec![].into_iter(),
+            fields: fields.into_iter(),
+            current: None,
+            ctx,
+            _p: PhantomData,
+        }
+    }
+
+    /// Adds the node's kind as `$kind` and its text as `$text`, for [`crate::KindTagged`].
+    pub fn with_kind(node: N, ctx: Rc<Context>) -> Result<Self, DeserializeError> {
+        let reserved = vec![
+            (crate::types::KIND_FIELD, node.kind()),
+            (crate::types::TEXT_FIELD, crate::tsnode::text(&node)?),
+        ];
+        Ok(FieldsAsMapAccess {
+            reserved: reserved.into_iter(),
+            ..Self::new(node, ctx)
+        })
+    }
+}
+impl<'de, N: TsNode<'de>> serde::de::MapAccess<'de> for FieldsAsMapAccess<'de, N> {
+    type Error = DeserializeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: serde::de::DeserializeSeed<'de>,
+    {
+        let key = if let Some((key, value)) = self.reserved.next() {
+            self.current = Some(Ok(value));
+            key
+        } else if let Some((field, nodes)) = self.fields.next() {
+            self.current = Some(Err((field, nodes)));
+            field
+        } else {
+            return Ok(None);
+        };
+        seed.deserialize(serde::de::value::BorrowedStrDeserializer::new(key))
+            .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::DeserializeSeed<'de>,
+    {
+        let current = self
+            .current
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        let (field, nodes) = match current {
+            Ok(value) => {
+                return seed.deserialize(serde::de::value::BorrowedStrDeserializer::new(value))
+            }
+            Err(field) => field,
+        };
+        let ctx = self.ctx.clone();
+        self.ctx.map_value(|| {
+            seed.deserialize(crate::deserializer::FieldDeserializer::new(
+                field, nodes, ctx,
+            ))
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.reserved.len() + self.fields.len())
+    }
+}
+
+/// Children assigned to fields, grouped by field names in order of their first appearance.
+fn group_by_field<'de, N: TsNode<'de>>(node: &N) -> Vec<(&'static str, Vec<N>)> {
+    let mut groups: Vec<(&'static str, Vec<N>)> = Vec::new();
+    for (field, child) in node.field_children() {
+        match groups.iter_mut().find(|(f, _)| *f == field) {
+            Some((_, nodes)) => nodes.push(child),
+            None => groups.push((field, vec![child])),
+        }
     }
+    groups
 }
@@ -0,0 +1,172 @@
+//! Memoization of deserialized values across incremental re-parses.
+//!
+//! Tree-sitter shares unchanged subtrees between the old and the new tree, so a node that keeps
+//! its identity and position can reuse the value deserialized from the previous tree.
+
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::types::{CACHED, NODE_KEY};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct CacheKey {
+    id: usize,
+    start: usize,
+    end: usize,
+    type_id: TypeId,
+}
+
+/// Cache for [`from_tree_cached`](crate::from_tree_cached).
+///
+/// Only values wrapped in [`Cached`] are stored. Entries not used in the latest deserialization
+/// are evicted.
+#[derive(Default)]
+pub struct DeserializeCache {
+    entries: HashMap<CacheKey, Box<dyn Any>>,
+    touched: HashSet<CacheKey>,
+    hits: usize,
+}
+
+impl fmt::Debug for DeserializeCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeserializeCache")
+            .field("len", &self.entries.len())
+            .field("hits", &self.hits)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DeserializeCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of cached values.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of values reused in the latest deserialization.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    fn get<T: Clone + 'static>(&mut self, key: CacheKey) -> Option<T> {
+        let value = self.entries.get(&key)?.downcast_ref::<T>()?.clone();
+        self.touched.insert(key);
+        self.hits += 1;
+        Some(value)
+    }
+
+    fn insert<T: 'static>(&mut self, key: CacheKey, value: T) {
+        self.entries.insert(key, Box::new(value));
+        self.touched.insert(key);
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<DeserializeCache>> = const { RefCell::new(None) };
+}
+
+/// Makes `cache` available to [`Cached`] values while running `f`.
+#[cfg(feature = "tree-sitter")]
+pub(crate) fn with_cache<R>(cache: &mut DeserializeCache, f: impl FnOnce() -> R) -> R {
+    let mut current = std::mem::take(cache);
+    current.touched.clear();
+    current.hits = 0;
+    let prev = CURRENT.with(|c| c.replace(Some(current)));
+    let result = f();
+    let mut current = CURRENT.with(|c| c.replace(prev)).unwrap();
+    let touched = std::mem::take(&mut current.touched);
+    current.entries.retain(|key, _| touched.contains(key));
+    current.touched = touched;
+    *cache = current;
+    result
+}
+
+/// A value that is reused from [`DeserializeCache`] if the node is unchanged.
+///
+/// Without a cache(e.g. in [`from_tree`](crate::from_tree)), this is same as `T`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Cached<T>(pub T);
+
+impl<'de, T: Deserialize<'de> + Clone + 'static> Deserialize<'de> for Cached<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor<T>(PhantomData<T>);
+        impl<'de, T: Deserialize<'de> + Clone + 'static> serde::de::Visitor<'de> for Visitor<T> {
+            type Value = Cached<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a node and its identity")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let NodeKey([id, start, end]) = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let key = CacheKey {
+                    id,
+                    start,
+                    end,
+                    type_id: TypeId::of::<T>(),
+                };
+                let hit = CURRENT.with(|c| c.borrow_mut().as_mut().and_then(|c| c.get::<T>(key)));
+                if let Some(value) = hit {
+                    return Ok(Cached(value));
+                }
+                let value: T = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(1, &self))?;
+                CURRENT.with(|c| {
+                    if let Some(c) = c.borrow_mut().as_mut() {
+                        c.insert(key, value.clone());
+                    }
+                });
+                Ok(Cached(value))
+            }
+        }
+        deserializer.deserialize_newtype_struct(CACHED, Visitor(PhantomData))
+    }
+}
+
+/// Identity and position of a node.
+struct NodeKey([usize; 3]);
+
+impl<'de> Deserialize<'de> for NodeKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor;
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = NodeKey;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("identity of a node")
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                <[usize; 3]>::deserialize(deserializer).map(NodeKey)
+            }
+        }
+        deserializer.deserialize_newtype_struct(NODE_KEY, Visitor)
+    }
+}
@@ -0,0 +1,195 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use crate::{
+    deserializer::PlaceholderDeserializer, tsnode::TsNode, DeserializeError, FromNodeOptions,
+};
+
+/// State shared by all deserializers created from one entry point.
+#[derive(Debug, Default)]
+pub(crate) struct Context {
+    pub options: FromNodeOptions,
+    elements: Cell<usize>,
+    trace: Option<RefCell<Trace>>,
+    errors: Option<RefCell<Vec<DeserializeError>>>,
+    /// Ids and kinds of the nodes being deserialized, from the root.
+    path: RefCell<Vec<(usize, &'static str)>>,
+    /// Ranges of the named children that were not consumed, if tracked.
+    unconsumed: RefCell<Vec<std::ops::Range<usize>>>,
+    /// Depth of map values being deserialized.
+    map_values: Cell<usize>,
+}
+
+/// Kinds of entered nodes, recorded by [`crate::from_tree_traced`].
+#[derive(Debug, Default)]
+struct Trace {
+    kinds: Vec<String>,
+    last_id: Option<usize>,
+}
+
+impl Context {
+    pub fn new(options: FromNodeOptions) -> Self {
+        Context {
+            options,
+            elements: Cell::new(0),
+            trace: None,
+            errors: None,
+            path: RefCell::default(),
+            unconsumed: RefCell::default(),
+            map_values: Cell::new(0),
+        }
+    }
+
+    #[cfg(feature = "tree-sitter")]
+    pub fn traced(options: FromNodeOptions) -> Self {
+        Context {
+            trace: Some(RefCell::default()),
+            ..Self::new(options)
+        }
+    }
+
+    /// Context that collects recoverable errors, used by [`crate::from_node_collecting`].
+    #[cfg(feature = "tree-sitter")]
+    pub fn collecting(options: FromNodeOptions) -> Self {
+        Context {
+            errors: Some(RefCell::default()),
+            ..Self::new(options)
+        }
+    }
+
+    /// Called when a node is about to be deserialized.
+    ///
+    /// Records the node's kind if tracing. Entering the same node repeatedly is recorded once.
+    /// The node stays in the path until the returned guard is dropped.
+    pub fn enter<'de, N: TsNode<'de>>(
+        self: &Rc<Self>,
+        node: &N,
+    ) -> Result<Option<Entered>, DeserializeError> {
+        if let Some(trace) = &self.trace {
+            let mut trace = trace.borrow_mut();
+            if trace.last_id != Some(node.id()) {
+                trace.last_id = Some(node.id());
+                trace.kinds.push(node.kind().to_owned());
+            }
+        }
+        if self.options.is_reject_error_nodes() && node.is_error() {
+            return Err(DeserializeError::ErrorNode(node.byte_range()));
+        }
+        let mut path = self.path.borrow_mut();
+        if path.last().map(|(id, _)| *id) == Some(node.id()) {
+            return Ok(None);
+        }
+        if let Some(limit) = self.options.get_max_depth() {
+            if path.len() >= limit {
+                return Err(DeserializeError::DepthLimitExceeded { limit }.at(node.byte_range()));
+            }
+        }
+        path.push((node.id(), node.kind()));
+        Ok(Some(Entered {
+            ctx: self.clone(),
+            depth: path.len() - 1,
+        }))
+    }
+
+    /// Kinds of the nodes from the root to `node`.
+    pub fn path_to<'de, N: TsNode<'de>>(&self, node: &N) -> Vec<&'static str> {
+        let path = self.path.borrow();
+        let mut kinds = path.iter().map(|(_, kind)| *kind).collect::<Vec<_>>();
+        if path.last().map(|(id, _)| *id) != Some(node.id()) {
+            kinds.push(node.kind());
+        }
+        kinds
+    }
+
+    #[cfg(feature = "tree-sitter")]
+    pub fn take_trace(&self) -> Vec<String> {
+        self.trace
+            .as_ref()
+            .map(|trace| std::mem::take(&mut trace.borrow_mut().kinds))
+            .unwrap_or_default()
+    }
+
+    #[cfg(feature = "tree-sitter")]
+    pub fn take_errors(&self) -> Vec<DeserializeError> {
+        self.errors
+            .as_ref()
+            .map(|errors| std::mem::take(&mut *errors.borrow_mut()))
+            .unwrap_or_default()
+    }
+
+    /// Fails with `err`, or records it and continues with a placeholder value if collecting
+    /// errors.
+    pub fn recover<T>(
+        &self,
+        err: DeserializeError,
+        placeholder: impl FnOnce(PlaceholderDeserializer) -> Result<T, DeserializeError>,
+    ) -> Result<T, DeserializeError> {
+        match &self.errors {
+            Some(errors) => {
+                errors.borrow_mut().push(err);
+                placeholder(PlaceholderDeserializer)
+            }
+            None => Err(err),
+        }
+    }
+
+    /// Records named children that were not consumed, if
+    /// [`FromNodeOptions::deny_unconsumed_children`] is set.
+    pub fn unconsumed<'de, N: TsNode<'de>>(&self, children: impl IntoIterator<Item = N>) {
+        if self.options.is_deny_unconsumed_children() {
+            let mut unconsumed = self.unconsumed.borrow_mut();
+            unconsumed.extend(children.into_iter().map(|c| c.byte_range()));
+        }
+    }
+
+    /// Fails if any named child was recorded as not consumed.
+    #[cfg(feature = "tree-sitter")]
+    pub fn check_unconsumed(&self) -> Result<(), DeserializeError> {
+        let ranges = std::mem::take(&mut *self.unconsumed.borrow_mut());
+        if ranges.is_empty() {
+            return Ok(());
+        }
+        Err(DeserializeError::UnconsumedChildren { ranges })
+    }
+
+    /// Runs `f` to deserialize a map value.
+    ///
+    /// serde buffers map values of flattened structs and internally tagged enums without knowing
+    /// their types, so `deserialize_any` offers leaf nodes in them as text instead of parsing it.
+    /// This lets string-typed fields of the buffered value read numbers and keywords.
+    pub fn map_value<R>(&self, f: impl FnOnce() -> R) -> R {
+        self.map_values.set(self.map_values.get() + 1);
+        let result = f();
+        self.map_values.set(self.map_values.get() - 1);
+        result
+    }
+
+    pub fn is_in_map_value(&self) -> bool {
+        self.map_values.get() > 0
+    }
+
+    /// Counts a sequence element, and fails if the total exceeds the limit.
+    pub fn count_element(&self) -> Result<(), DeserializeError> {
+        let elements = self.elements.get() + 1;
+        self.elements.set(elements);
+        match self.options.get_element_limit() {
+            Some(limit) if limit < elements => {
+                Err(DeserializeError::ElementLimitExceeded { limit })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Keeps an entered node in the path of [`Context`] until dropped.
+#[derive(Debug)]
+pub(crate) struct Entered {
+    ctx: Rc<Context>,
+    depth: usize,
+}
+
+impl Drop for Entered {
+    fn drop(&mut self) {
+        self.ctx.path.borrow_mut().truncate(self.depth);
+    }
+}
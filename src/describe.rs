@@ -0,0 +1,536 @@
+//! Human-readable description of what a type expects from the syntax tree.
+//!
+//! [`describe`] feeds a probe deserializer to the type's `Deserialize` impl and records every
+//! request. The probe answers with placeholder values: sequences are probed with a single element,
+//! and enums are listed by their variant kinds without describing the variants' contents.
+
+use std::cell::{Cell, RefCell};
+
+use serde::de::value::BorrowedStrDeserializer;
+use serde::Deserialize;
+
+use crate::{types, DeserializeError};
+
+/// Limit of nesting depth while building placeholder values.
+const MAX_DEPTH: usize = 64;
+
+/// Describes the tree structure that `T` expects, one line per requested value.
+///
+/// ```
+/// #[derive(serde::Deserialize)]
+/// #[serde(rename = "pair")]
+/// struct Pair {
+///     key: String,
+///     value: Option<u32>,
+/// }
+/// assert_eq!(
+///     serde_tree_sitter::describe::<Pair>(),
+///     "node `pair` with fields\n  field `key`: text\n  field `value`: optional\n    integer",
+/// );
+/// ```
+pub fn describe<'de, T: Deserialize<'de>>() -> String {
+    let recorder = Recorder::default();
+    let _ = T::deserialize(Probe {
+        recorder: &recorder,
+        indent: 0,
+        label: String::new(),
+        silent: false,
+        tail: false,
+    });
+    recorder.lines.into_inner().join("\n")
+}
+
+#[derive(Default)]
+struct Recorder {
+    lines: RefCell<Vec<String>>,
+    stack: RefCell<Vec<&'static str>>,
+    depth: Cell<usize>,
+    /// Whether the last element of the tuple being described is a sequence.
+    tail_seq: Cell<bool>,
+}
+
+struct Probe<'r> {
+    recorder: &'r Recorder,
+    indent: usize,
+    label: String,
+    silent: bool,
+    /// The last element of a tuple, that takes the remaining children if it is a sequence.
+    tail: bool,
+}
+
+impl<'r> Probe<'r> {
+    fn line(&self, desc: &str) -> String {
+        format!("{}{}{}", "  ".repeat(self.indent), self.label, desc)
+    }
+
+    fn write(&self, desc: &str) {
+        if !self.silent {
+            self.recorder.lines.borrow_mut().push(self.line(desc));
+        }
+    }
+
+    /// Visits the elements of a tuple. If the last element is a sequence, rewrites the line at
+    /// `index` with `desc` of the minimum number of children.
+    fn tuple_elements<'de, V: serde::de::Visitor<'de>>(
+        &self,
+        len: usize,
+        index: usize,
+        desc: impl FnOnce(String) -> String,
+        visitor: V,
+    ) -> Result<V::Value, DeserializeError> {
+        let children = (0..len).map(|i| Probe {
+            tail: i + 1 == len,
+            ..self.child(format!("child {i}: "))
+        });
+        let outer = self.recorder.tail_seq.replace(false);
+        let result = visitor.visit_seq(ElementsSeq(children.collect::<Vec<_>>().into_iter()));
+        if self.recorder.tail_seq.replace(outer) && !self.silent {
+            let line = self.line(&desc(format!("{} or more named children", len - 1)));
+            self.recorder.lines.borrow_mut()[index] = line;
+        }
+        result
+    }
+
+    fn child(&self, label: impl Into<String>) -> Probe<'r> {
+        Probe {
+            recorder: self.recorder,
+            indent: self.indent + 1,
+            label: label.into(),
+            silent: self.silent,
+            tail: false,
+        }
+    }
+
+    fn silent(&self) -> Probe<'r> {
+        Probe {
+            recorder: self.recorder,
+            indent: self.indent,
+            label: String::new(),
+            silent: true,
+            tail: false,
+        }
+    }
+
+    /// Describes a named type, and builds its value with `f`.
+    ///
+    /// A type that is already being described is marked as recursive and built silently.
+    fn named<T>(
+        self,
+        name: &'static str,
+        desc: &str,
+        f: impl FnOnce(Probe<'r>) -> Result<T, DeserializeError>,
+    ) -> Result<T, DeserializeError> {
+        let depth = self.recorder.depth.get();
+        if MAX_DEPTH <= depth {
+            return Err(DeserializeError::Custom("Too deep".into()));
+        }
+        let recursive = !self.silent && self.recorder.stack.borrow().contains(&name);
+        let probe = if recursive {
+            self.write(&format!("{desc} (recursive)"));
+            self.silent()
+        } else {
+            self.write(desc);
+            self
+        };
+        let push = !probe.silent;
+        if push {
+            probe.recorder.stack.borrow_mut().push(name);
+        }
+        probe.recorder.depth.set(depth + 1);
+        let recorder = probe.recorder;
+        let result = f(probe);
+        recorder.depth.set(depth);
+        if push {
+            recorder.stack.borrow_mut().pop();
+        }
+        result
+    }
+}
+
+macro_rules! describe_primitive {
+    ($name:ident, $visit:ident($($value:expr)?), $desc:literal) => {
+        fn $name<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: serde::de::Visitor<'de>,
+        {
+            self.write($desc);
+            visitor.$visit($($value)?)
+        }
+    };
+}
+
+impl<'de, 'r> serde::Deserializer<'de> for Probe<'r> {
+    type Error = DeserializeError;
+
+    describe_primitive!(deserialize_any, visit_unit(), "any");
+    describe_primitive!(deserialize_bool, visit_bool(false), "bool");
+    describe_primitive!(deserialize_i8, visit_i8(0), "integer");
+    describe_primitive!(deserialize_i16, visit_i16(0), "integer");
+    describe_primitive!(deserialize_i32, visit_i32(0), "integer");
+    describe_primitive!(deserialize_i64, visit_i64(0), "integer");
+    describe_primitive!(deserialize_i128, visit_i128(0), "integer");
+    describe_primitive!(deserialize_u8, visit_u8(0), "integer");
+    describe_primitive!(deserialize_u16, visit_u16(0), "integer");
+    describe_primitive!(deserialize_u32, visit_u32(0), "integer");
+    describe_primitive!(deserialize_u64, visit_u64(0), "integer");
+    describe_primitive!(deserialize_u128, visit_u128(0), "integer");
+    describe_primitive!(deserialize_f32, visit_f32(0.0), "float");
+    describe_primitive!(deserialize_f64, visit_f64(0.0), "float");
+    describe_primitive!(deserialize_char, visit_char(' '), "char");
+    describe_primitive!(deserialize_str, visit_borrowed_str(""), "text");
+    describe_primitive!(deserialize_string, visit_borrowed_str(""), "text");
+    describe_primitive!(deserialize_bytes, visit_borrowed_bytes(b""), "bytes");
+    describe_primitive!(deserialize_byte_buf, visit_borrowed_bytes(b""), "bytes");
+    describe_primitive!(deserialize_unit, visit_unit(), "()");
+    describe_primitive!(deserialize_identifier, visit_borrowed_str(""), "kind");
+    describe_primitive!(deserialize_ignored_any, visit_unit(), "ignored");
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        if self.silent {
+            return visitor.visit_none();
+        }
+        self.write("optional");
+        visitor.visit_some(self.child(""))
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.write(&format!("node `{name}`"));
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        if types::is_reserved_name(name) {
+            let wrapper = name.rsplit("::").next().unwrap_or(name);
+            return match name {
+                types::MAYBE_KIND
+                | types::LOCATED
+                | types::POSITIONED
+                | types::PRESENCE
+                | types::CACHED => {
+                    self.write(wrapper);
+                    let inner = self.child("");
+                    visitor.visit_seq(WrapperSeq {
+                        probes: [Some(self.silent()), Some(inner)],
+                        index: 0,
+                    })
+                }
+                types::BY_CHILD => {
+                    self.write(wrapper);
+                    visitor.visit_newtype_struct(self.child("child: "))
+                }
+                types::FIRST | types::LAST | types::NEXT_SIBLING => {
+                    self.write(wrapper);
+                    visitor.visit_newtype_struct(self.child(""))
+                }
+                types::TREE_VALUE => {
+                    self.write("any node");
+                    serde::Deserializer::deserialize_newtype_struct(
+                        crate::deserializer::PlaceholderDeserializer,
+                        name,
+                        visitor,
+                    )
+                }
+                types::ALL_CHILDREN => {
+                    self.write(wrapper);
+                    let each = self.child("each: ");
+                    visitor.visit_seq(ElementsSeq(vec![each].into_iter()))
+                }
+                _ => {
+                    self.write(wrapper);
+                    visitor.visit_newtype_struct(self.silent())
+                }
+            };
+        }
+        self.named(name, &format!("node `{name}`"), |probe| {
+            let inner = probe.child("");
+            visitor.visit_newtype_struct(inner)
+        })
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        if self.tail {
+            self.recorder.tail_seq.set(true);
+        }
+        if self.silent {
+            return visitor.visit_seq(ElementsSeq(std::iter::empty::<Probe>()));
+        }
+        self.write(if self.tail {
+            "remaining named children"
+        } else {
+            "named children"
+        });
+        let each = self.child("each: ");
+        visitor.visit_seq(ElementsSeq(vec![each].into_iter()))
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let index = self.recorder.lines.borrow().len();
+        self.write(&format!("{len} named children"));
+        self.tuple_elements(len, index, |children| children, visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.named(
+            name,
+            &format!("node `{name}` with {len} named children"),
+            |probe| {
+                let index = probe.recorder.lines.borrow().len().saturating_sub(1);
+                probe.tuple_elements(
+                    len,
+                    index,
+                    |children| format!("node `{name}` with {children}"),
+                    visitor,
+                )
+            },
+        )
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.write("map");
+        visitor.visit_map(serde::de::value::MapDeserializer::new(std::iter::empty::<(
+            (),
+            (),
+        )>()))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.named(name, &format!("node `{name}` with fields"), |probe| {
+            let fields = fields
+                .iter()
+                .map(|f| {
+                    if types::is_reserved_field(f) {
+                        probe.child(format!("`{f}` of the node itself: "))
+                    } else if *f == types::REST_FIELD {
+                        probe.child(format!("`{f}` children not in other fields: "))
+                    } else {
+                        probe.child(format!("field `{f}`: "))
+                    }
+                })
+                .collect::<Vec<_>>();
+            visitor.visit_seq(ElementsSeq(fields.into_iter()))
+        })
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let mut kinds = variants
+            .iter()
+            .filter(|v| **v != types::OTHER_VARIANT)
+            .map(|v| format!("`{v}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if variants.contains(&types::OTHER_VARIANT) {
+            kinds.push_str(", or any other kind");
+        }
+        self.named(name, &format!("one of kinds {kinds}"), |probe| {
+            let Some(variant) = variants.first() else {
+                return Err(DeserializeError::Custom("Empty enum".into()));
+            };
+            visitor.visit_enum(VariantProbe {
+                variant,
+                probe: probe.silent(),
+            })
+        })
+    }
+}
+
+/// Elements of sequences, tuples and structs.
+struct ElementsSeq<I>(I);
+
+impl<'de, 'r, I: Iterator<Item = Probe<'r>>> serde::de::SeqAccess<'de> for ElementsSeq<I> {
+    type Error = DeserializeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        match self.0.next() {
+            Some(probe) => seed.deserialize(probe).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Elements of wrapper types that read the node twice.
+struct WrapperSeq<'r> {
+    probes: [Option<Probe<'r>>; 2],
+    index: usize,
+}
+
+impl<'de, 'r> serde::de::SeqAccess<'de> for WrapperSeq<'r> {
+    type Error = DeserializeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        let Some(probe) = self.probes.get_mut(self.index).and_then(Option::take) else {
+            return Ok(None);
+        };
+        self.index += 1;
+        if self.index == 1 {
+            // Answer the presence/kind check positively so that the inner value is requested
+            return seed.deserialize(FirstElementProbe(probe)).map(Some);
+        }
+        seed.deserialize(probe).map(Some)
+    }
+}
+
+/// Probe for the first element of [`WrapperSeq`], that reports `true` for booleans.
+struct FirstElementProbe<'r>(Probe<'r>);
+
+impl<'de, 'r> serde::Deserializer<'de> for FirstElementProbe<'r> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.0.deserialize_any(visitor)
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_bool(true)
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.0.deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.0.deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.0.deserialize_identifier(visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit seq tuple tuple_struct map struct enum ignored_any
+    }
+}
+
+/// Selects a variant of an enum and builds its content silently.
+struct VariantProbe<'r> {
+    variant: &'static str,
+    probe: Probe<'r>,
+}
+
+impl<'de, 'r> serde::de::EnumAccess<'de> for VariantProbe<'r> {
+    type Error = DeserializeError;
+    type Variant = Probe<'r>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: serde::de::DeserializeSeed<'de>,
+    {
+        let value = seed.deserialize(BorrowedStrDeserializer::<DeserializeError>::new(
+            self.variant,
+        ))?;
+        Ok((value, self.probe))
+    }
+}
+
+impl<'de, 'r> serde::de::VariantAccess<'de> for Probe<'r> {
+    type Error = DeserializeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        serde::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let fields = fields.iter().map(|_| self.silent()).collect::<Vec<_>>();
+        visitor.visit_seq(ElementsSeq(fields.into_iter()))
+    }
+}
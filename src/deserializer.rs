@@ -1,7 +1,15 @@
+#[cfg(feature = "tree-sitter")]
+pub mod captures;
 pub mod field;
 pub mod newtype_struct;
 pub mod node;
+pub mod placeholder;
+pub mod tail;
 
+#[cfg(feature = "tree-sitter")]
+pub use captures::CapturesDeserializer;
 pub use field::FieldDeserializer;
 pub use newtype_struct::NewtypeStructDeserializer;
 pub use node::NodeDeserializer;
+pub use placeholder::PlaceholderDeserializer;
+pub use tail::TailDeserializer;
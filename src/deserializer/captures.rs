@@ -0,0 +1,77 @@
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use crate::context::Context;
+use crate::tsnode::TsNode;
+use crate::DeserializeError;
+
+/// Deserializer for the captures of a query match, that are mapped to struct fields by capture
+/// names.
+///
+/// Each field takes all nodes captured with its name, so a field captured more than once must be
+/// a sequence, and a field not captured must be a sequence or an `Option`. Captures without
+/// corresponding field are ignored.
+pub struct CapturesDeserializer<'q, 'de, N: TsNode<'de>> {
+    captures: Vec<(&'q str, Vec<N>)>,
+    ctx: Rc<Context>,
+    _p: PhantomData<&'de ()>,
+}
+impl<'q, 'de, N: TsNode<'de>> CapturesDeserializer<'q, 'de, N> {
+    pub fn new(captures: impl IntoIterator<Item = (&'q str, N)>, ctx: Rc<Context>) -> Self {
+        let mut groups: Vec<(&'q str, Vec<N>)> = Vec::new();
+        for (name, node) in captures {
+            match groups.iter_mut().find(|(n, _)| *n == name) {
+                Some((_, nodes)) => nodes.push(node),
+                None => groups.push((name, vec![node])),
+            }
+        }
+        CapturesDeserializer {
+            captures: groups,
+            ctx,
+            _p: PhantomData,
+        }
+    }
+}
+
+impl<'q, 'de, N: TsNode<'de>> serde::de::Deserializer<'de> for CapturesDeserializer<'q, 'de, N> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        Err(DeserializeError::DataTypeNotSupported(
+            "Query match captures can only be deserialized into a struct".into(),
+        ))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let mut captures = self.captures;
+        let fields = fields
+            .iter()
+            .map(
+                |field| match captures.iter().position(|(name, _)| name == field) {
+                    Some(index) => (*field, captures.swap_remove(index).1),
+                    None => (*field, vec![]),
+                },
+            )
+            .collect();
+        visitor.visit_map(crate::access::FieldsAsMapAccess::from_fields(
+            fields, self.ctx,
+        ))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
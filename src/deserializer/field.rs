@@ -1,5 +1,8 @@
+use serde::de::IntoDeserializer;
 use std::marker::PhantomData;
+use std::rc::Rc;
 
+use crate::context::Context;
 use crate::deserializer::NodeDeserializer;
 use crate::tsnode::TsNode;
 use crate::DeserializeError;
@@ -7,13 +10,15 @@ use crate::DeserializeError;
 pub struct FieldDeserializer<'de, N: TsNode<'de>> {
     field_name: &'static str,
     nodes: Vec<N>,
-    _p: PhantomData<&'de N>,
+    ctx: Rc<Context>,
+    _p: PhantomData<&'de ()>,
 }
 impl<'de, N: TsNode<'de>> FieldDeserializer<'de, N> {
-    pub fn new(field_name: &'static str, nodes: Vec<N>) -> Self {
+    pub fn new(field_name: &'static str, nodes: Vec<N>, ctx: Rc<Context>) -> Self {
         FieldDeserializer {
             field_name,
             nodes,
+            ctx,
             _p: PhantomData,
         }
     }
@@ -28,7 +33,7 @@ impl<'de, N: TsNode<'de>> FieldDeserializer<'de, N> {
                 self.nodes.len(),
             ));
         }
-        f(NodeDeserializer::new(self.nodes.pop().unwrap()))
+        f(NodeDeserializer::enter(self.nodes.pop().unwrap(), self.ctx)?.in_field(self.field_name))
     }
 }
 
@@ -53,10 +58,12 @@ impl<'de, N: TsNode<'de>> serde::de::Deserializer<'de> for FieldDeserializer<'de
         deserialize_i16,
         deserialize_i32,
         deserialize_i64,
+        deserialize_i128,
         deserialize_u8,
         deserialize_u16,
         deserialize_u32,
         deserialize_u64,
+        deserialize_u128,
         deserialize_f32,
         deserialize_f64,
         deserialize_char,
@@ -70,14 +77,16 @@ impl<'de, N: TsNode<'de>> serde::de::Deserializer<'de> for FieldDeserializer<'de
         deserialize_ignored_any,
     );
 
-    fn deserialize_option<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        match self.nodes.len() {
-            0 => visitor.visit_none(),
-            1 => visitor.visit_some(NodeDeserializer::new(self.nodes.pop().unwrap())),
-            n => Err(DeserializeError::field_length(self.field_name, 1, n)),
+        // The number of nodes is checked by the inner type, so that `Option<Vec<T>>` takes all
+        // of them.
+        if self.nodes.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
         }
     }
 
@@ -94,34 +103,70 @@ impl<'de, N: TsNode<'de>> serde::de::Deserializer<'de> for FieldDeserializer<'de
 
     fn deserialize_newtype_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        self.delegate(move |de| de.deserialize_newtype_struct(_name, visitor))
+        if name == crate::types::FLAG {
+            return match self.nodes.len() {
+                0 => visitor.visit_newtype_struct(false.into_deserializer()),
+                1 => visitor.visit_newtype_struct(true.into_deserializer()),
+                n => Err(DeserializeError::field_length(self.field_name, 1, n)),
+            };
+        }
+        if name == crate::types::FIRST || name == crate::types::LAST {
+            let node = if name == crate::types::FIRST {
+                self.nodes.into_iter().next()
+            } else {
+                self.nodes.into_iter().next_back()
+            };
+            let Some(node) = node else {
+                return Err(DeserializeError::field_length(self.field_name, 1, 0));
+            };
+            return visitor.visit_newtype_struct(
+                NodeDeserializer::enter(node, self.ctx)?.in_field(self.field_name),
+            );
+        }
+        if name == crate::types::JOINED {
+            let text = self
+                .nodes
+                .iter()
+                .map(crate::tsnode::text)
+                .collect::<Result<String, DeserializeError>>()?;
+            return visitor.visit_newtype_struct(text.into_deserializer());
+        }
+        if name == crate::types::PRESENCE {
+            let present = !self.nodes.is_empty();
+            return visitor.visit_seq(crate::access::PresenceAccess::new(present, self));
+        }
+        self.delegate(move |de| de.deserialize_newtype_struct(name, visitor))
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        visitor.visit_seq(crate::access::SeqAccess::new(self.nodes.into_iter()))
+        visitor.visit_seq(crate::access::SeqAccess::new(
+            self.nodes.into_iter(),
+            self.ctx,
+        ))
     }
 
     fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        if self.nodes.len() != len {
-            return Err(DeserializeError::field_length(
-                self.field_name,
-                len,
-                self.nodes.len(),
-            ));
+        // The last element may be a sequence that takes the rest, so it can be empty.
+        let count = self.nodes.len();
+        let lenient = self.ctx.options.is_lenient_tuple();
+        if count < len.saturating_sub(1) || (len == 0 && count != 0 && !lenient) {
+            return Err(DeserializeError::field_length(self.field_name, len, count));
         }
-        visitor.visit_seq(crate::access::SeqAccess::new(self.nodes.into_iter()))
+        visitor.visit_seq(
+            crate::access::TupleAccess::new(self.nodes, len, self.ctx).in_field(self.field_name),
+        )
     }
 
     fn deserialize_tuple_struct<V>(
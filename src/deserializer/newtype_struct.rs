@@ -1,5 +1,7 @@
 use std::marker::PhantomData;
+use std::rc::Rc;
 
+use crate::context::{Context, Entered};
 use crate::deserializer::NodeDeserializer;
 use crate::tsnode::TsNode;
 use crate::{access::SeqAccess, DeserializeError};
@@ -7,28 +9,39 @@ use crate::{access::SeqAccess, DeserializeError};
 pub struct NewtypeStructDeserializer<'de, N: TsNode<'de>> {
     node: N,
     name: &'static str,
-    _p: PhantomData<&'de N>,
+    ctx: Rc<Context>,
+    /// Nodes kept in the path while this deserializer is alive.
+    entered: Vec<Entered>,
+    _p: PhantomData<&'de ()>,
 }
 
 impl<'de, N: TsNode<'de>> NewtypeStructDeserializer<'de, N> {
-    pub fn new(name: &'static str, node: N) -> Self {
+    pub fn new(name: &'static str, node: N, ctx: Rc<Context>) -> Self {
         Self {
             node,
             name,
+            ctx,
+            entered: vec![],
             _p: PhantomData,
         }
     }
+    /// Keeps the entered nodes in the path while this deserializer is alive.
+    pub(crate) fn keep(mut self, entered: impl IntoIterator<Item = Entered>) -> Self {
+        self.entered.extend(entered);
+        self
+    }
     fn try_into_single_child_deserializer(
         self,
     ) -> Result<NodeDeserializer<'de, N>, DeserializeError> {
-        let mut children = self.node.named_children();
-        if children.len() != 1 {
-            return Err(DeserializeError::child_length(1, children.len()));
+        let count = self.node.named_child_count();
+        if count != 1 {
+            return Err(DeserializeError::child_length(1, count));
         }
-        Ok(NodeDeserializer::new(children.next().unwrap()))
+        let child = self.node.named_children().next().unwrap();
+        Ok(NodeDeserializer::enter(child, self.ctx)?.keep(self.entered))
     }
     fn into_node_deserializer(self) -> NodeDeserializer<'de, N> {
-        NodeDeserializer::new(self.node)
+        NodeDeserializer::new(self.node, self.ctx).keep(self.entered)
     }
     fn err_not_supported<T>(&self, name: &str) -> Result<T, DeserializeError> {
         Err(DeserializeError::DataTypeNotSupported(format!(
@@ -62,31 +75,29 @@ macro_rules! delegate_to_node_serializer {
 impl<'de, N: TsNode<'de>> serde::Deserializer<'de> for NewtypeStructDeserializer<'de, N> {
     type Error = DeserializeError;
 
-    not_supported!(
-        deserialize_any,
-        deserialize_char,
-        deserialize_bytes,
-        deserialize_byte_buf,
-        deserialize_map,
-        deserialize_identifier,
-    );
+    not_supported!(deserialize_any, deserialize_map, deserialize_identifier,);
 
     delegate_to_node_serializer!(
         deserialize_unit,
         deserialize_bool,
+        deserialize_char,
         deserialize_i8,
         deserialize_i16,
         deserialize_i32,
         deserialize_i64,
+        deserialize_i128,
         deserialize_u8,
         deserialize_u16,
         deserialize_u32,
         deserialize_u64,
+        deserialize_u128,
         deserialize_f32,
         deserialize_f64,
         deserialize_ignored_any,
         deserialize_str,
         deserialize_string,
+        deserialize_bytes,
+        deserialize_byte_buf,
     );
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -119,6 +130,14 @@ impl<'de, N: TsNode<'de>> serde::Deserializer<'de> for NewtypeStructDeserializer
     where
         V: serde::de::Visitor<'de>,
     {
+        if name == crate::types::FIRST || name == crate::types::LAST {
+            return visitor.visit_newtype_struct(self);
+        }
+        if crate::types::is_reserved_name(name) {
+            return self
+                .into_node_deserializer()
+                .deserialize_newtype_struct(name, visitor);
+        }
         self.try_into_single_child_deserializer()?
             .deserialize_newtype_struct(name, visitor)
     }
@@ -127,18 +146,24 @@ impl<'de, N: TsNode<'de>> serde::Deserializer<'de> for NewtypeStructDeserializer
     where
         V: serde::de::Visitor<'de>,
     {
-        visitor.visit_seq(SeqAccess::new(self.node.named_children()))
+        visitor.visit_seq(SeqAccess::new(self.node.named_children(), self.ctx))
     }
 
     fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        let children = self.node.named_children();
-        if len != children.len() {
-            return Err(DeserializeError::child_length(len, children.len()));
+        // Same as a tuple of the node's named children.
+        let child_count = self.node.named_child_count();
+        let lenient = self.ctx.options.is_lenient_tuple();
+        if child_count < len.saturating_sub(1) || (len == 0 && child_count != 0 && !lenient) {
+            return Err(DeserializeError::child_length(len, child_count));
         }
-        visitor.visit_seq(crate::access::SeqAccess::new(children))
+        visitor.visit_seq(crate::access::TupleAccess::new(
+            self.node.named_children().collect(),
+            len,
+            self.ctx,
+        ))
     }
 
     fn deserialize_tuple_struct<V>(
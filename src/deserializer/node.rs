@@ -1,40 +1,263 @@
-use crate::{access::FieldsAsSeqAccess, tsnode::TsNode, DeserializeError};
+use crate::{
+    access::FieldsAsSeqAccess,
+    context::{Context, Entered},
+    tsnode::TsNode,
+    types, DeserializeError,
+};
+use serde::de::IntoDeserializer;
+use std::borrow::Cow;
 use std::marker::PhantomData;
+use std::rc::Rc;
+
+/// Integers that can be parsed in any radix.
+trait FromStrRadix: std::str::FromStr<Err = std::num::ParseIntError> {
+    fn from_str_radix(src: &str, radix: u32) -> Result<Self, std::num::ParseIntError>;
+}
+
+macro_rules! impl_from_str_radix {
+    ($($t:ty)*) => {
+        $(impl FromStrRadix for $t {
+            fn from_str_radix(src: &str, radix: u32) -> Result<Self, std::num::ParseIntError> {
+                <$t>::from_str_radix(src, radix)
+            }
+        })*
+    };
+}
+impl_from_str_radix!(u8 u16 u32 u64 u128 i8 i16 i32 i64 i128);
+
+/// Parses an integer with an optional sign and `0x`/`0o`/`0b` prefix.
+fn parse_radix_prefixed<T: FromStrRadix>(text: &str) -> Result<T, std::num::ParseIntError> {
+    let (sign, digits) = match text.strip_prefix(['-', '+']) {
+        Some(digits) => (&text[..1], digits),
+        None => ("", text),
+    };
+    let (radix, digits) = match digits.get(..2) {
+        Some("0x" | "0X") => (16, &digits[2..]),
+        Some("0o" | "0O") => (8, &digits[2..]),
+        Some("0b" | "0B") => (2, &digits[2..]),
+        _ => (10, digits),
+    };
+    if let Some(inner_sign) = digits.get(..1).filter(|c| *c == "-" || *c == "+") {
+        // `from_str_radix` accepts a sign, but not after the prefix. A lone sign is invalid.
+        return T::from_str_radix(inner_sign, radix);
+    }
+    if sign == "-" {
+        T::from_str_radix(&format!("-{digits}"), radix)
+    } else {
+        T::from_str_radix(digits, radix)
+    }
+}
+
+/// Removes `_` digit separators if each of them is between alphanumeric characters. Otherwise,
+/// returns the text as is to fail in parsing.
+fn strip_digit_separators(text: Cow<'_, str>) -> Cow<'_, str> {
+    let bytes = text.as_bytes();
+    let valid = bytes.iter().enumerate().all(|(i, b)| {
+        *b != b'_'
+            || (i > 0
+                && bytes[i - 1].is_ascii_alphanumeric()
+                && bytes.get(i + 1).is_some_and(u8::is_ascii_alphanumeric))
+    });
+    if valid && text.contains('_') {
+        Cow::Owned(text.replace('_', ""))
+    } else {
+        text
+    }
+}
 
 pub struct NodeDeserializer<'de, N: TsNode<'de>> {
     node: N,
-    _p: PhantomData<&'de N>,
+    ctx: Rc<Context>,
+    field_name: Option<&'static str>,
+    /// Nodes kept in the path while this deserializer is alive.
+    entered: Vec<Entered>,
+    /// Only checks the kind for [`crate::MaybeKind`], without recording unconsumed children.
+    kind_probe: bool,
+    _p: PhantomData<&'de ()>,
 }
 impl<'de, N: TsNode<'de>> NodeDeserializer<'de, N> {
-    fn parse_int<T: std::str::FromStr<Err = std::num::ParseIntError>>(
-        &self,
-    ) -> Result<T, DeserializeError> {
-        self.node
-            .src()
-            .parse::<T>()
-            .map_err(DeserializeError::ParseIntError)
+    /// Marks the node as the content of a struct field, for error reporting.
+    pub fn in_field(mut self, field_name: &'static str) -> Self {
+        self.field_name = Some(field_name);
+        self
+    }
+    fn check_kind(&self, name: &str) -> Result<(), DeserializeError> {
+        if self.ctx.options.kind_matches(name, self.node.kind()) {
+            return Ok(());
+        }
+        let err = match self.field_name {
+            Some(field_name) => {
+                DeserializeError::field_node_type(field_name, name, self.node.kind())
+            }
+            None => DeserializeError::node_type(name, self.node.kind()),
+        };
+        Err(err
+            .at(self.node.byte_range())
+            .with_path(self.ctx.path_to(&self.node)))
+    }
+    /// Descends into single-child wrapper nodes until the kind matches, as configured by
+    /// [`crate::FromNodeOptions::unwrap_single_child`]. Returns `self` if no such node is found.
+    fn unwrap_to(mut self, matches: impl Fn(&N) -> bool) -> Result<Self, DeserializeError> {
+        let depth = self.ctx.options.get_unwrap_depth();
+        if depth == 0 || matches(&self.node) {
+            return Ok(self);
+        }
+        let mut wrapped = vec![];
+        let mut node = self.node.clone();
+        for _ in 0..depth {
+            if node.named_child_count() != 1 {
+                break;
+            }
+            node = node.named_child(0).unwrap();
+            wrapped.push(node.clone());
+            if matches(&node) {
+                for child in &wrapped {
+                    self.entered.extend(self.ctx.enter(child)?);
+                }
+                self.node = node;
+                return Ok(self);
+            }
+        }
+        Ok(self)
+    }
+    fn unwrap_to_kind(self, name: &str) -> Result<Self, DeserializeError> {
+        let ctx = self.ctx.clone();
+        self.unwrap_to(|node| ctx.options.kind_matches(name, node.kind()))
+    }
+    fn text(&self) -> Result<&'de str, DeserializeError> {
+        crate::tsnode::text(&self.node)
+    }
+    fn number_text(&self) -> Result<Cow<'de, str>, DeserializeError> {
+        let text = self.ctx.options.normalize_for_parse(self.text()?);
+        if self.ctx.options.is_allow_digit_separators() || self.ctx.options.is_radix_prefixes() {
+            Ok(strip_digit_separators(text))
+        } else {
+            Ok(text)
+        }
+    }
+    fn parse_int<T: FromStrRadix>(&self) -> Result<T, DeserializeError> {
+        let src = self.text()?;
+        let text = self.number_text()?;
+        let parsed = if self.ctx.options.is_radix_prefixes() {
+            parse_radix_prefixed(&text)
+        } else {
+            text.parse::<T>()
+        };
+        parsed.map_err(|e| {
+            DeserializeError::parse_int(e, src, self.node.kind()).at(self.node.byte_range())
+        })
     }
     fn parse_float<T: std::str::FromStr<Err = std::num::ParseFloatError>>(
         &self,
     ) -> Result<T, DeserializeError> {
-        self.node
-            .src()
-            .parse::<T>()
-            .map_err(DeserializeError::ParseFloatError)
+        let src = self.text()?;
+        self.number_text()?.parse::<T>().map_err(|e| {
+            DeserializeError::parse_float(e, src, self.node.kind()).at(self.node.byte_range())
+        })
     }
     fn parse_bool<T: std::str::FromStr<Err = std::str::ParseBoolError>>(
         &self,
     ) -> Result<T, DeserializeError> {
-        self.node
-            .src()
-            .parse::<T>()
-            .map_err(DeserializeError::ParseBoolError)
+        let src = self.text()?;
+        let text = self.ctx.options.normalize_for_parse(src);
+        let text = match self.ctx.options.bool_literal(&text) {
+            Some(value) => Cow::Borrowed(if value { "true" } else { "false" }),
+            None => text,
+        };
+        text.parse::<T>().map_err(|e| {
+            DeserializeError::parse_bool(e, src, self.node.kind()).at(self.node.byte_range())
+        })
+    }
+    fn parse_char(&self) -> Result<char, DeserializeError> {
+        let src = self.text()?;
+        let mut chars = src.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(DeserializeError::InvalidChar(src.to_owned()).at(self.node.byte_range())),
+        }
+    }
+    fn deserialize_reserved<V>(self, name: &str, visitor: V) -> Result<V::Value, DeserializeError>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        match name {
+            types::CHILD_KINDS => {
+                let kinds = self.node.named_children().map(|c| c.kind());
+                visitor.visit_newtype_struct(serde::de::value::SeqDeserializer::new(kinds))
+            }
+            types::NODE_KIND => visitor.visit_newtype_struct(self.node.kind().into_deserializer()),
+            types::NODE_ID => visitor.visit_newtype_struct(self.node.id().into_deserializer()),
+            types::RAW_TEXT => visitor
+                .visit_newtype_struct(serde::de::value::BorrowedStrDeserializer::new(self.text()?)),
+            types::ALL_CHILDREN => visitor.visit_seq(crate::access::SeqAccess::new(
+                self.node.children(),
+                self.ctx,
+            )),
+            types::KIND_TAGGED => {
+                let map = crate::access::FieldsAsMapAccess::with_kind(self.node, self.ctx)?;
+                visitor.visit_newtype_struct(serde::de::value::MapAccessDeserializer::new(map))
+            }
+            types::TREE_VALUE => {
+                visitor.visit_seq(crate::access::TreeValueAccess::new(self.node, self.ctx))
+            }
+            types::BY_CHILD => {
+                let mut children = self.node.named_children().collect::<Vec<_>>();
+                if children.len() != 1 {
+                    return Err(DeserializeError::child_length(1, children.len()));
+                }
+                visitor.visit_newtype_struct(NodeDeserializer::enter(
+                    children.pop().unwrap(),
+                    self.ctx,
+                )?)
+            }
+            types::PRESENCE => {
+                let value = NodeDeserializer::new(self.node, self.ctx);
+                visitor.visit_seq(crate::access::PresenceAccess::new(true, value))
+            }
+            types::FLAG => visitor.visit_newtype_struct(true.into_deserializer()),
+            types::FIRST | types::LAST => visitor.visit_newtype_struct(self),
+            types::NEXT_SIBLING => {
+                let Some(sibling) = self.node.next_named_sibling() else {
+                    return Err(DeserializeError::NoNextSibling.at(self.node.byte_range()));
+                };
+                visitor.visit_newtype_struct(NodeDeserializer::enter(sibling, self.ctx)?)
+            }
+            types::JOINED => {
+                visitor.visit_newtype_struct(self.text()?.to_owned().into_deserializer())
+            }
+            types::MAYBE_KIND | types::LOCATED | types::POSITIONED | types::CACHED => {
+                let mut first = NodeDeserializer::new(self.node.clone(), self.ctx.clone());
+                first.kind_probe = name == types::MAYBE_KIND;
+                visitor.visit_seq(crate::access::WrapperAccess::new(first, self))
+            }
+            types::NODE_KEY => {
+                let range = self.node.byte_range();
+                visitor.visit_newtype_struct(serde::de::value::SeqDeserializer::new(
+                    [self.node.id(), range.start, range.end].into_iter(),
+                ))
+            }
+            types::SPAN => {
+                let range = self.node.byte_range();
+                visitor.visit_newtype_struct(serde::de::value::SeqDeserializer::new(
+                    [range.start, range.end].into_iter(),
+                ))
+            }
+            types::POSITION => {
+                let (start, end) = (self.node.start_position(), self.node.end_position());
+                visitor.visit_newtype_struct(serde::de::value::SeqDeserializer::new(
+                    [start.row, start.column, end.row, end.column].into_iter(),
+                ))
+            }
+            _ => Err(DeserializeError::DataTypeNotSupported(format!(
+                "Unknown reserved name: {name}"
+            ))),
+        }
     }
     fn into_newtype_struct_deserializer(
         self,
         name: &'static str,
     ) -> crate::deserializer::NewtypeStructDeserializer<'de, N> {
-        super::NewtypeStructDeserializer::new(name, self.node)
+        super::NewtypeStructDeserializer::new(name, self.node, self.ctx).keep(self.entered)
     }
 }
 
@@ -44,7 +267,12 @@ macro_rules! handle_primitive {
         where
             V: serde::de::Visitor<'de>,
         {
-            visitor.$visit(self.$parse()?)
+            match self.$parse() {
+                Ok(value) => visitor.$visit(value),
+                Err(err) => self
+                    .ctx
+                    .recover(err, |p| serde::Deserializer::$name(p, visitor)),
+            }
         }
     };
 }
@@ -56,7 +284,35 @@ impl<'de, N: TsNode<'de>> serde::Deserializer<'de> for NodeDeserializer<'de, N>
     where
         V: serde::de::Visitor<'de>,
     {
-        visitor.visit_seq(crate::access::SeqAccess::new(self.node.named_children()))
+        // Leaf nodes are self-describing as their text, parsed as the first primitive type it
+        // fits. This lets untagged enums try their primitive variants.
+        if self.node.named_child_count() == 0 {
+            let text = self.text()?;
+            if self.ctx.is_in_map_value() {
+                return visitor.visit_borrowed_str(text);
+            }
+            if let Ok(value) = self.parse_bool() {
+                return visitor.visit_bool(value);
+            }
+            if let Ok(value) = self.parse_int() {
+                return visitor.visit_i64(value);
+            }
+            if let Ok(value) = self.parse_int() {
+                return visitor.visit_u64(value);
+            }
+            // Not `inf` or `nan`, that may be identifiers
+            let unsigned = text.strip_prefix(['+', '-']).unwrap_or(text);
+            if unsigned.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+                if let Ok(value) = self.parse_float() {
+                    return visitor.visit_f64(value);
+                }
+            }
+            return visitor.visit_borrowed_str(text);
+        }
+        visitor.visit_seq(crate::access::SeqAccess::new(
+            self.node.named_children(),
+            self.ctx,
+        ))
     }
 
     handle_primitive!(deserialize_bool, parse_bool, visit_bool);
@@ -64,50 +320,44 @@ impl<'de, N: TsNode<'de>> serde::Deserializer<'de> for NodeDeserializer<'de, N>
     handle_primitive!(deserialize_u16, parse_int, visit_u16);
     handle_primitive!(deserialize_u32, parse_int, visit_u32);
     handle_primitive!(deserialize_u64, parse_int, visit_u64);
+    handle_primitive!(deserialize_u128, parse_int, visit_u128);
     handle_primitive!(deserialize_i8, parse_int, visit_i8);
     handle_primitive!(deserialize_i16, parse_int, visit_i16);
     handle_primitive!(deserialize_i32, parse_int, visit_i32);
     handle_primitive!(deserialize_i64, parse_int, visit_i64);
+    handle_primitive!(deserialize_i128, parse_int, visit_i128);
     handle_primitive!(deserialize_f32, parse_float, visit_f32);
     handle_primitive!(deserialize_f64, parse_float, visit_f64);
 
-    fn deserialize_char<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: serde::de::Visitor<'de>,
-    {
-        Err(DeserializeError::DataTypeNotSupported(
-            "Data type `char` is not supported".into(),
-        ))
-    }
+    handle_primitive!(deserialize_char, parse_char, visit_char);
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        visitor.visit_borrowed_str(self.node.src())
+        visitor.visit_borrowed_str(self.text()?)
     }
 
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        visitor.visit_string(self.node.src().to_owned())
+        // Visitors for owned strings copy the borrowed text only when they need to.
+        visitor.visit_borrowed_str(self.text()?)
     }
 
     fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        visitor.visit_borrowed_bytes(self.node.src().as_bytes())
+        visitor.visit_borrowed_bytes(self.node.src_bytes())
     }
 
-    fn deserialize_byte_buf<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        Err(DeserializeError::DataTypeNotSupported(
-            "Data type `byte_buf` is not supported".into(),
-        ))
+        visitor.visit_byte_buf(self.node.src_bytes().to_vec())
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -117,7 +367,7 @@ impl<'de, N: TsNode<'de>> serde::Deserializer<'de> for NodeDeserializer<'de, N>
         let mut children = self.node.named_children().collect::<Vec<_>>();
         match children.len() {
             0 => visitor.visit_none(),
-            1 => visitor.visit_some(NodeDeserializer::new(children.pop().unwrap())),
+            1 => visitor.visit_some(NodeDeserializer::enter(children.pop().unwrap(), self.ctx)?),
             n => Err(DeserializeError::child_length(1, n)),
         }
     }
@@ -137,8 +387,10 @@ impl<'de, N: TsNode<'de>> serde::Deserializer<'de> for NodeDeserializer<'de, N>
     where
         V: serde::de::Visitor<'de>,
     {
-        if name != self.node.kind() {
-            return Err(DeserializeError::node_type(name, self.node.kind()));
+        let de = self.unwrap_to_kind(name)?;
+        de.check_kind(name)?;
+        if !de.kind_probe {
+            de.ctx.unconsumed(de.node.named_children());
         }
         visitor.visit_unit()
     }
@@ -151,17 +403,23 @@ impl<'de, N: TsNode<'de>> serde::Deserializer<'de> for NodeDeserializer<'de, N>
     where
         V: serde::de::Visitor<'de>,
     {
-        if name != self.node.kind() {
-            return Err(DeserializeError::node_type(name, self.node.kind()));
+        if types::is_reserved_name(name) {
+            return self.deserialize_reserved(name, visitor);
+        }
+        let de = self.unwrap_to_kind(name)?;
+        if let Err(err) = de.check_kind(name) {
+            return de.ctx.recover(err, |p| {
+                serde::Deserializer::deserialize_newtype_struct(p, name, visitor)
+            });
         }
-        visitor.visit_newtype_struct(self.into_newtype_struct_deserializer(name))
+        visitor.visit_newtype_struct(de.into_newtype_struct_deserializer(name))
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        let seq_access = crate::access::SeqAccess::new(self.node.named_children());
+        let seq_access = crate::access::SeqAccess::new(self.node.named_children(), self.ctx);
         visitor.visit_seq(seq_access)
     }
 
@@ -169,13 +427,17 @@ impl<'de, N: TsNode<'de>> serde::Deserializer<'de> for NodeDeserializer<'de, N>
     where
         V: serde::de::Visitor<'de>,
     {
-        if len != self.node.named_child_count() {
-            return Err(DeserializeError::child_length(
-                len,
-                self.node.named_child_count(),
-            ));
+        // The last element may be a sequence that takes the rest, so it can be empty.
+        let child_count = self.node.named_child_count();
+        let lenient = self.ctx.options.is_lenient_tuple();
+        if child_count < len.saturating_sub(1) || (len == 0 && child_count != 0 && !lenient) {
+            return Err(DeserializeError::child_length(len, child_count));
         }
-        self.deserialize_seq(visitor)
+        visitor.visit_seq(crate::access::TupleAccess::new(
+            self.node.named_children().collect(),
+            len,
+            self.ctx,
+        ))
     }
 
     fn deserialize_tuple_struct<V>(
@@ -187,19 +449,20 @@ impl<'de, N: TsNode<'de>> serde::Deserializer<'de> for NodeDeserializer<'de, N>
     where
         V: serde::de::Visitor<'de>,
     {
-        if name != self.node.kind() {
-            return Err(DeserializeError::node_type(name, self.node.kind()));
+        let de = self.unwrap_to_kind(name)?;
+        if let Err(err) = de.check_kind(name) {
+            return de.ctx.recover(err, |p| {
+                serde::Deserializer::deserialize_tuple_struct(p, name, len, visitor)
+            });
         }
-        self.deserialize_tuple(len, visitor)
+        de.deserialize_tuple(len, visitor)
     }
 
-    fn deserialize_map<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        Err(DeserializeError::DataTypeNotSupported(
-            "Data type `map` is not supported".into(),
-        ))
+        visitor.visit_map(crate::access::FieldsAsMapAccess::new(self.node, self.ctx))
     }
 
     fn deserialize_struct<V>(
@@ -211,22 +474,32 @@ impl<'de, N: TsNode<'de>> serde::Deserializer<'de> for NodeDeserializer<'de, N>
     where
         V: serde::de::Visitor<'de>,
     {
-        if name != self.node.kind() {
-            return Err(DeserializeError::node_type(name, self.node.kind()));
+        let de = self.unwrap_to_kind(name)?;
+        if let Err(err) = de.check_kind(name) {
+            return de.ctx.recover(err, |p| {
+                serde::Deserializer::deserialize_struct(p, name, fields, visitor)
+            });
         }
-        visitor.visit_seq(FieldsAsSeqAccess::new(self.node, fields))
+        FieldsAsSeqAccess::new(de.node, fields, de.ctx).visit(visitor)
     }
 
     fn deserialize_enum<V>(
         self,
         name: &'static str,
-        _variants: &'static [&'static str],
+        variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        let enum_access = crate::access::EnumAccess::new(self.node, name);
+        let ctx = self.ctx.clone();
+        let de = self.unwrap_to(|node| {
+            let kind = ctx.options.normalize(node.kind());
+            variants.contains(&types::OTHER_VARIANT)
+                || variants.iter().any(|v| ctx.options.kind_matches(v, &kind))
+                || ctx.options.variant_by_child_kind(name, node).is_some()
+        })?;
+        let enum_access = crate::access::EnumAccess::new(de.node, name, variants, de.ctx);
         visitor.visit_enum(enum_access)
     }
 
@@ -234,7 +507,10 @@ impl<'de, N: TsNode<'de>> serde::Deserializer<'de> for NodeDeserializer<'de, N>
     where
         V: serde::de::Visitor<'de>,
     {
-        visitor.visit_borrowed_str(self.node.kind())
+        match self.ctx.options.normalize(self.node.kind()) {
+            Cow::Borrowed(kind) => visitor.visit_borrowed_str(kind),
+            Cow::Owned(kind) => visitor.visit_string(kind),
+        }
     }
 
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -245,10 +521,24 @@ impl<'de, N: TsNode<'de>> serde::Deserializer<'de> for NodeDeserializer<'de, N>
     }
 }
 impl<'de, N: TsNode<'de>> NodeDeserializer<'de, N> {
-    pub fn new(node: N) -> NodeDeserializer<'de, N> {
+    pub fn new(node: N, ctx: Rc<Context>) -> NodeDeserializer<'de, N> {
         NodeDeserializer {
             node,
+            ctx,
+            field_name: None,
+            entered: vec![],
+            kind_probe: false,
             _p: PhantomData,
         }
     }
+    /// Same as `new`, for a node that is entered for the first time(e.g. a child node).
+    pub fn enter(node: N, ctx: Rc<Context>) -> Result<NodeDeserializer<'de, N>, DeserializeError> {
+        let entered = ctx.enter(&node)?;
+        Ok(Self::new(node, ctx).keep(entered))
+    }
+    /// Keeps the entered nodes in the path while this deserializer is alive.
+    pub(crate) fn keep(mut self, entered: impl IntoIterator<Item = Entered>) -> Self {
+        self.entered.extend(entered);
+        self
+    }
 }
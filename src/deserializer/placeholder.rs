@@ -0,0 +1,240 @@
+use crate::{types, DeserializeError};
+
+/// Deserializer that answers every request with an empty value, used in place of a value that
+/// failed to deserialize while collecting errors.
+///
+/// Numbers are zero, strings are empty, options are `None`, sequences are empty, and enums take
+/// the first variant.
+pub struct PlaceholderDeserializer;
+
+macro_rules! placeholder_primitive {
+    ($($name:ident => $visit:ident($value:expr),)*) => {
+        $(
+            fn $name<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: serde::de::Visitor<'de>,
+            {
+                visitor.$visit($value)
+            }
+        )*
+    };
+}
+
+impl<'de> serde::Deserializer<'de> for PlaceholderDeserializer {
+    type Error = DeserializeError;
+
+    placeholder_primitive! {
+        deserialize_bool => visit_bool(false),
+        deserialize_i8 => visit_i8(0),
+        deserialize_i16 => visit_i16(0),
+        deserialize_i32 => visit_i32(0),
+        deserialize_i64 => visit_i64(0),
+        deserialize_i128 => visit_i128(0),
+        deserialize_u8 => visit_u8(0),
+        deserialize_u16 => visit_u16(0),
+        deserialize_u32 => visit_u32(0),
+        deserialize_u64 => visit_u64(0),
+        deserialize_u128 => visit_u128(0),
+        deserialize_f32 => visit_f32(0.0),
+        deserialize_f64 => visit_f64(0.0),
+        deserialize_char => visit_char('\0'),
+        deserialize_str => visit_borrowed_str(""),
+        deserialize_string => visit_borrowed_str(""),
+        deserialize_bytes => visit_borrowed_bytes(b""),
+        deserialize_byte_buf => visit_borrowed_bytes(b""),
+        deserialize_identifier => visit_borrowed_str(""),
+    }
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_none()
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        match name {
+            types::MAYBE_KIND
+            | types::LOCATED
+            | types::POSITIONED
+            | types::PRESENCE
+            | types::CACHED => visitor.visit_seq(PlaceholderSeq(2)),
+            types::TREE_VALUE => visitor.visit_seq(PlaceholderSeq(4)),
+            _ => visitor.visit_newtype_struct(self),
+        }
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_seq(PlaceholderSeq(0))
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_seq(PlaceholderSeq(len))
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_seq(PlaceholderSeq(len))
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_map(serde::de::value::MapDeserializer::new(std::iter::empty::<(
+            (),
+            (),
+        )>()))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_seq(PlaceholderSeq(fields.len()))
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        let Some(variant) = variants.first() else {
+            return Err(DeserializeError::DataTypeNotSupported(
+                "Enum without variants".into(),
+            ));
+        };
+        visitor.visit_enum(PlaceholderEnum(variant))
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+}
+
+/// Sequence of the given number of placeholders.
+struct PlaceholderSeq(usize);
+
+impl<'de> serde::de::SeqAccess<'de> for PlaceholderSeq {
+    type Error = DeserializeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        if self.0 == 0 {
+            return Ok(None);
+        }
+        self.0 -= 1;
+        seed.deserialize(PlaceholderDeserializer).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0)
+    }
+}
+
+struct PlaceholderEnum(&'static str);
+
+impl<'de> serde::de::EnumAccess<'de> for PlaceholderEnum {
+    type Error = DeserializeError;
+    type Variant = PlaceholderDeserializer;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: serde::de::DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(serde::de::value::BorrowedStrDeserializer::new(self.0))?;
+        Ok((variant, PlaceholderDeserializer))
+    }
+}
+
+impl<'de> serde::de::VariantAccess<'de> for PlaceholderDeserializer {
+    type Error = DeserializeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_seq(PlaceholderSeq(len))
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_seq(PlaceholderSeq(fields.len()))
+    }
+}
@@ -0,0 +1,171 @@
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use crate::context::Context;
+use crate::deserializer::NodeDeserializer;
+use crate::tsnode::TsNode;
+use crate::DeserializeError;
+
+/// Deserializer for the last element of a tuple.
+///
+/// Sequences consume all remaining children. Any other types require exact one remaining child,
+/// or take the first one if the tuple is lenient.
+pub struct TailDeserializer<'de, N: TsNode<'de>> {
+    nodes: Vec<N>,
+    len: usize,
+    child_count: usize,
+    /// Field that the tuple matches, for error reporting.
+    field_name: Option<&'static str>,
+    ctx: Rc<Context>,
+    _p: PhantomData<&'de ()>,
+}
+impl<'de, N: TsNode<'de>> TailDeserializer<'de, N> {
+    pub fn new(nodes: Vec<N>, len: usize, child_count: usize, ctx: Rc<Context>) -> Self {
+        TailDeserializer {
+            nodes,
+            len,
+            child_count,
+            field_name: None,
+            ctx,
+            _p: PhantomData,
+        }
+    }
+    /// Marks the tuple as the content of a struct field, for error reporting.
+    pub fn in_field(mut self, field_name: Option<&'static str>) -> Self {
+        self.field_name = field_name;
+        self
+    }
+    fn delegate<F, R>(self, f: F) -> Result<R, DeserializeError>
+    where
+        F: FnOnce(NodeDeserializer<'de, N>) -> Result<R, DeserializeError>,
+    {
+        let lenient = self.ctx.options.is_lenient_tuple();
+        if self.nodes.is_empty() || (self.nodes.len() > 1 && !lenient) {
+            return Err(match self.field_name {
+                Some(field_name) => {
+                    DeserializeError::field_length(field_name, self.len, self.child_count)
+                }
+                None => DeserializeError::child_length(self.len, self.child_count),
+            });
+        }
+        let node = self.nodes.into_iter().next().unwrap();
+        f(NodeDeserializer::enter(node, self.ctx)?)
+    }
+}
+
+macro_rules! delegate_to_node_deserializer {
+    () => {};
+    ($name:ident, $($rest:ident ,)*$(,)?) => {
+        fn $name<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: serde::de::Visitor<'de> {
+            self.delegate(move |de| de.$name(visitor))
+        }
+        delegate_to_node_deserializer!($($rest,)*);
+    };
+}
+
+impl<'de, N: TsNode<'de>> serde::de::Deserializer<'de> for TailDeserializer<'de, N> {
+    type Error = DeserializeError;
+
+    delegate_to_node_deserializer!(
+        deserialize_any,
+        deserialize_bool,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_i128,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_u128,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_char,
+        deserialize_str,
+        deserialize_bytes,
+        deserialize_byte_buf,
+        deserialize_string,
+        deserialize_option,
+        deserialize_map,
+        deserialize_unit,
+        deserialize_identifier,
+        deserialize_ignored_any,
+    );
+
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.delegate(move |de| de.deserialize_unit_struct(name, visitor))
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.delegate(move |de| de.deserialize_newtype_struct(name, visitor))
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_seq(crate::access::SeqAccess::new(
+            self.nodes.into_iter(),
+            self.ctx,
+        ))
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.delegate(move |de| de.deserialize_tuple(len, visitor))
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.delegate(move |de| de.deserialize_tuple_struct(name, len, visitor))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.delegate(|de| de.deserialize_struct(name, fields, visitor))
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.delegate(move |de| de.deserialize_enum(name, variants, visitor))
+    }
+}
@@ -10,23 +10,126 @@ pub enum DeserializeError {
         expected: usize,
         actual: usize,
     },
+    #[error("Node kind `{kind}` matched variant `{variant}` of `{enum_name}`, but child count not match: expected={expected}, actual={actual}")]
+    VariantChildLength {
+        enum_name: &'static str,
+        kind: &'static str,
+        variant: &'static str,
+        expected: usize,
+        actual: usize,
+    },
     #[error("Node type not match: expected={expected}, actual={actual}")]
     NodeType { expected: String, actual: String },
+    #[error("Unknown variant `{kind}`, expected one of: [{}]", .expected.iter().map(|v| format!("`{v}`")).collect::<Vec<_>>().join(", "))]
+    UnknownVariant {
+        kind: String,
+        expected: Vec<&'static str>,
+    },
+    #[error("in field `{field_name}`: node type expected `{expected}`, actual `{actual}`")]
+    FieldNodeType {
+        field_name: &'static str,
+        expected: String,
+        actual: String,
+    },
     #[error("{0}")]
     DataTypeNotSupported(String),
+    #[error("Failed to parse `{text}`(node kind `{kind}`) as integer: {source}")]
+    ParseIntError {
+        source: std::num::ParseIntError,
+        text: String,
+        kind: &'static str,
+    },
+    #[error("Failed to parse `{text}`(node kind `{kind}`) as float: {source}")]
+    ParseFloatError {
+        source: std::num::ParseFloatError,
+        text: String,
+        kind: &'static str,
+    },
+    #[error("Failed to parse `{text}`(node kind `{kind}`) as bool: {source}")]
+    ParseBoolError {
+        source: std::str::ParseBoolError,
+        text: String,
+        kind: &'static str,
+    },
     #[error(transparent)]
-    ParseIntError(std::num::ParseIntError),
-    #[error(transparent)]
-    ParseFloatError(std::num::ParseFloatError),
+    Utf8Error(std::str::Utf8Error),
+    #[error("Expected a single character, actual `{0}`")]
+    InvalidChar(String),
+    #[error("No next named sibling")]
+    NoNextSibling,
+    #[error("Too many elements: limit={limit}")]
+    ElementLimitExceeded { limit: usize },
+    #[error("Named children not consumed at bytes: {ranges:?}")]
+    UnconsumedChildren { ranges: Vec<std::ops::Range<usize>> },
+    #[error("Too deeply nested: limit={limit}")]
+    DepthLimitExceeded { limit: usize },
+    #[error("All alternatives failed: [{}]", .0.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; "))]
+    AllAlternativesFailed(Vec<DeserializeError>),
+    #[error("Unknown capture name: {0}")]
+    UnknownCapture(String),
+    #[error("Tree-sitter error node at {0:?}")]
+    ErrorNode(std::ops::Range<usize>),
+    #[cfg(feature = "tree-sitter")]
     #[error(transparent)]
-    ParseBoolError(std::str::ParseBoolError),
-    #[error("Tree-sitter node contain error(s)")]
-    TreeSitterError(Vec<tree_sitter::Range>),
+    LanguageError(tree_sitter::LanguageError),
+    #[error("Tree-sitter failed to parse the source")]
+    ParseFailed,
+    #[error("Tree-sitter node contain error(s): [{}]", .0.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; "))]
+    TreeSitterError(Vec<SyntaxError>),
+    #[error("{error} at bytes {}..{}{}", .range.start, .range.end, fmt_path(.path))]
+    AtNode {
+        range: std::ops::Range<usize>,
+        /// Kinds of the nodes from the root to the node, if known.
+        path: Vec<&'static str>,
+        error: Box<DeserializeError>,
+    },
     #[error("{0}")]
     Custom(String),
 }
 
 impl DeserializeError {
+    /// Attaches the byte range of the node that caused the error.
+    pub fn at(self, range: std::ops::Range<usize>) -> Self {
+        DeserializeError::AtNode {
+            range,
+            path: vec![],
+            error: Box::new(self),
+        }
+    }
+    /// Attaches the kinds of the nodes from the root to the node that caused the error.
+    /// Has no effect unless the byte range is attached.
+    pub fn with_path(mut self, kinds: Vec<&'static str>) -> Self {
+        if let DeserializeError::AtNode { path, .. } = &mut self {
+            *path = kinds;
+        }
+        self
+    }
+    /// Kinds of the nodes from the root to the node that caused the error, if known.
+    pub fn path(&self) -> &[&'static str] {
+        match self {
+            DeserializeError::AtNode { path, .. } => path,
+            _ => &[],
+        }
+    }
+    /// Byte range of the node that caused the error, if known.
+    ///
+    /// For [`DeserializeError::TreeSitterError`], this is the range of the first syntax error.
+    pub fn range(&self) -> Option<std::ops::Range<usize>> {
+        match self {
+            DeserializeError::AtNode { range, .. } | DeserializeError::ErrorNode(range) => {
+                Some(range.clone())
+            }
+            DeserializeError::TreeSitterError(errors) => errors.first().map(|e| e.range.clone()),
+            _ => None,
+        }
+    }
+    /// The error without the attached byte range.
+    pub fn without_range(&self) -> &DeserializeError {
+        match self {
+            DeserializeError::AtNode { error, .. } => error,
+            _ => self,
+        }
+    }
     pub fn node_type<S1: Into<String>, S2: Into<String>>(
         expected: S1,
         actual: S2,
@@ -36,9 +139,68 @@ impl DeserializeError {
             actual: actual.into(),
         }
     }
+    pub fn field_node_type<S1: Into<String>, S2: Into<String>>(
+        field_name: &'static str,
+        expected: S1,
+        actual: S2,
+    ) -> DeserializeError {
+        DeserializeError::FieldNodeType {
+            field_name,
+            expected: expected.into(),
+            actual: actual.into(),
+        }
+    }
+    pub fn parse_int(
+        source: std::num::ParseIntError,
+        text: impl Into<String>,
+        kind: &'static str,
+    ) -> Self {
+        DeserializeError::ParseIntError {
+            source,
+            text: text.into(),
+            kind,
+        }
+    }
+    pub fn parse_float(
+        source: std::num::ParseFloatError,
+        text: impl Into<String>,
+        kind: &'static str,
+    ) -> Self {
+        DeserializeError::ParseFloatError {
+            source,
+            text: text.into(),
+            kind,
+        }
+    }
+    pub fn parse_bool(
+        source: std::str::ParseBoolError,
+        text: impl Into<String>,
+        kind: &'static str,
+    ) -> Self {
+        DeserializeError::ParseBoolError {
+            source,
+            text: text.into(),
+            kind,
+        }
+    }
     pub fn child_length(expected: usize, actual: usize) -> Self {
         DeserializeError::ChildLength { expected, actual }
     }
+    pub fn variant_child_length(
+        enum_name: &'static str,
+        kind: &'static str,
+        variant: &'static str,
+        expected: usize,
+        actual: usize,
+    ) -> Self {
+        DeserializeError::VariantChildLength {
+            enum_name,
+            kind,
+            variant,
+            expected,
+            actual,
+        }
+    }
     pub fn field_length(field_name: &'static str, expected: usize, actual: usize) -> Self {
         DeserializeError::FieldLength {
             field_name,
@@ -56,4 +218,73 @@ impl serde::de::Error for DeserializeError {
         // dbg!(std::backtrace::Backtrace::capture());
         DeserializeError::Custom(msg.to_string())
     }
+
+    fn unknown_variant(variant: &str, expected: &'static [&'static str]) -> Self {
+        DeserializeError::UnknownVariant {
+            kind: variant.to_owned(),
+            expected: expected.to_vec(),
+        }
+    }
+}
+
+/// Error or missing node in a tree, reported by [`DeserializeError::TreeSitterError`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub struct SyntaxError {
+    pub kind: SyntaxErrorKind,
+    pub range: std::ops::Range<usize>,
+    pub start: crate::Point,
+    pub end: crate::Point,
+    /// Source text of the node. Empty for a missing node.
+    pub src: String,
+}
+
+#[cfg(feature = "tree-sitter")]
+impl SyntaxError {
+    pub(crate) fn new(node: tree_sitter::Node, src: &[u8]) -> Self {
+        let kind = if node.is_missing() {
+            SyntaxErrorKind::Missing(node.kind())
+        } else {
+            SyntaxErrorKind::Error
+        };
+        SyntaxError {
+            kind,
+            range: node.byte_range(),
+            start: node.start_position(),
+            end: node.end_position(),
+            src: String::from_utf8_lossy(&src[node.byte_range()]).into_owned(),
+        }
+    }
+}
+
+impl Display for SyntaxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at {}:{}",
+            self.kind,
+            self.start.row + 1,
+            self.start.column + 1
+        )?;
+        match self.kind {
+            SyntaxErrorKind::Error => write!(f, ": `{}`", self.src),
+            SyntaxErrorKind::Missing(_) => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum SyntaxErrorKind {
+    /// `ERROR` node, that contains the text tree-sitter failed to parse.
+    #[error("ERROR")]
+    Error,
+    /// `MISSING` node of the kind, inserted by tree-sitter to recover from the error.
+    #[error("MISSING `{0}`")]
+    Missing(&'static str),
+}
+
+fn fmt_path(path: &[&str]) -> String {
+    if path.is_empty() {
+        return String::new();
+    }
+    format!(" in `{}`", path.join(" > "))
 }
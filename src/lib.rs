@@ -1,15 +1,31 @@
 //! Serde Deserializer for tree-sitter.
 //!
 //! Use [`from_tree`] or [`from_node`] function to map tree-sitter's parse result to any type you
-//! want.
+//! want. [`from_tree_with_check`] and [`from_node_with_check`] can skip the check for tree-sitter
+//! errors. [`from_tree_with_options`] and [`from_node_with_options`] accept [`FromNodeOptions`] to
+//! customize the mapping. [`from_node_collecting`] reports as many errors as possible at once.
+//! [`field_from_node`] deserializes a single field of a node without defining a struct.
+//! [`from_node_bytes`] and [`from_node_bytes_with_options`] accept a source that may not be valid
+//! UTF-8. [`from_node_seed`] passes external state to the values through a `DeserializeSeed`.
+//! [`iter_from_tree`] deserializes top-level items lazily.
+//! [`from_query_match`] deserializes captures of a query match into a struct.
+//! [`deserialize_query`] deserializes all captured nodes of a query, and
+//! [`deserialize_query_collecting`] skips the nodes that fail instead of failing on the first one.
+//! [`from_str`] parses the source before deserializing. [`from_ts_node`] deserializes other tree
+//! representations that implement [`TsNode`].
+//!
+//! Everything that takes tree-sitter's types is behind the `tree-sitter` feature, which is enabled
+//! by default. Without it, the crate doesn't depend on tree-sitter and [`from_ts_node`] is the
+//! entry point.
 //!
 //! # Mapping rules
 //!
 //! ## Root types(`R`)
 //!
 //! * Atom types
-//! * Tuple
+//! * Tuple, and array(`[R; N]`) as a tuple of N elements
 //! * `Vec<R>`
+//! * `Option<R>`
 //! * Unit struct(`struct Foo;`)
 //! * Newtype struct(`struct Foo(N)`)
 //! * Tuple struct(`struct Foo(T1, T2)`)
@@ -19,83 +35,687 @@
 //!  * `NewtypeVariant(N)`
 //!  * `TupleVariant(R1, R2)`
 //!  * `StructVariant{f1: F1, f2: F2}`
+//!  * `#[serde(rename = "$other")] Other(String)`: Matches any kind that no other variant
+//!    matches, and captures the kind. `#[serde(other)] Other` is also supported if the kind is
+//!    not needed.
+//! * Map(`HashMap<String, F>`): Keys are the field names of the node's children.
+//!
+//! Tuples and tuple structs match exact N named children, taken in source order whether or not
+//! they are assigned to fields. Use a struct to match them by field.
+//!
+//! A trailing `Vec<R>` member takes all the remaining named children instead of one child, e.g.
+//! `struct Call(Name, Vec<Arg>)` for `call: $ => seq($.name, repeat($.arg))`. This applies to
+//! root tuples and tuple structs, tuples in newtype structs, and tuples in struct fields.
+//!
+//! **Breaking change:** Previously the trailing `Vec<R>` matched a single child and took that
+//! child's named children, like any other member. For `seq($.name, $.argument_list)`, a type like
+//! `(Name, Vec<Arg>)` now takes the `argument_list` node itself as an `Arg`. To match the list
+//! node, wrap the `Vec` in a newtype struct: `(Name, ArgumentList)` with
+//! `#[serde(rename = "argument_list")] struct ArgumentList(Vec<Arg>)`.
+//!
+//! Empty ones(`[R; 0]`, `struct Foo()`) require the node to have no named children. `()` and unit
+//! structs(`struct Foo;`) ignore the children instead.
+//!
+//! Like `Vec<R>`, `Option<R>` matches the node's named children: 0 or 1 named child. It doesn't
+//! mean the node itself may be absent, since a node is always there. This is different from
+//! `Option<R>` in a struct field, that matches 0 or 1 node in the field.
+//!
+//! Structs with `#[serde(flatten)]` fields are deserialized as maps, so the node kind is not
+//! checked. Flattened fields are buffered as text: leaf nodes can be read as string-like types.
+//!
+//! For wrapper nodes like `statement` whose single named child's kind(`if_statement`,
+//! `while_statement`, ...) determines the variant, use [`ByChild`] or
+//! [`FromNodeOptions::unwrap_single_child`]. The variant is selected by the child's kind, and
+//! its data is deserialized from the child.
+//!
+//! `#[serde(untagged)]` enums try their variants against what `deserialize_any` offers: a node
+//! without named children is its text, parsed as `bool`, `i64`, `u64` or `f64` if possible, and
+//! other nodes are sequences of their named children. E.g.
+//! `enum Value { Int(i64), Leaf(String), List(Vec<Value>) }` matches `1` as `Int` and `a` as
+//! `Leaf`. Since parsed text is not a string, a `String` variant doesn't match `1`. Values of
+//! flattened fields are not parsed, as they are buffered as text.
 //!
 //! ## Atom types
 //!
 //! * `()`
 //! * `String`, `&str`, `&[u8]`
 //! * `bool`
-//! * Number types: `(u|i)(8|16|32|64)` and `f(32|64)`
+//! * Number types: `(u|i)(8|16|32|64|128)` and `f(32|64)`. Floats accept `inf`, `infinity` and
+//!   `nan` with optional sign in any case(e.g. `-Infinity`, `NaN`).
+//! * `char`: The node's text must be exactly one character.
+//!
+//! Other string types that deserialize from a string(`Box<str>`, `CompactString`, etc.) match the
+//! node's text as well. The `compact_str` feature enables serde support of `compact_str`.
+//!
+//! `&str` and `&[u8]` borrow the node's source, in struct fields as well. So do `Cow<str>` and
+//! `Cow<[u8]>` with `#[serde(borrow)]`. Note that `Vec<u8>` is not an atom type: serde
+//! deserializes it as a sequence, so it follows the `Vec<R>` rule and each named child is parsed
+//! as an `u8`.
 //!
 //! ## Newtype struct member type(`N`)
 //!
 //! * Atom types: Matches the node itself.
 //! * `Vec<R>`: Matches named children.
 //! * `Option<R>` Matches 0 or 1 named child.
-//! * `(R1, R2, ..., RN)`: Matches exact N named children.
+//! * `(R1, R2, ..., RN)`: Matches exact N named children, as a root tuple does. The trailing
+//!   `Vec<R>` takes all the remaining named children.
 //! * Structs/Enums: Matches exact one named child.
 //!
 //! ## Field member types(`F`)
 //!
-//! * `(R1, R2, ..., RN)`: Matches exact N named children in the field.
+//! * `(R1, R2, ..., RN)`: Matches exact N named children in the field. As a root tuple, the
+//!   trailing `Vec<R>` takes all the remaining nodes in the field.
 //! * `Vec<R>`: Matches named children in the field.
 //! * `Option<R>` Matches 0 or 1 named child in the field.
+//! * `Option<F>`: `None` if the field has no node, otherwise `Some` of the nodes matched as `F`,
+//!   e.g. `Option<Vec<R>>` is `Some` of all the nodes in the field.
 //! * Any other root types: If there is exact one node in the field, matches against it.
+//!
+//! Other sequence types(`Box<[R]>`, `Rc<[R]>`, `Arc<[R]>`, `VecDeque<R>`, sets, etc.) match the
+//! same nodes as `Vec<R>`.
+//!
+//! If a field has no node and its type needs one, the field's default value is used if it has
+//! `#[serde(default)]`.
+//!
+//! ## Special types
+//!
+//! * [`Span`]: Byte range of the node. `Vec<Span>` captures ranges of the named children.
+//! * [`Located`]: Value of the node with its [`Span`].
+//! * [`Spanned`]: Value of the node with its byte range.
+//! * [`Positioned`]: Value of the node with its start and end rows/columns.
+//! * [`RawText`]: Source text of the node as is, even if the node is a struct.
+//! * [`NodeKind`]: Kind of the node instead of its text, e.g. which operator matched.
+//! * [`NodeId`]: Identifier of the node, to find out references to the same node in a tree.
+//! * [`ChildKinds`]: Kinds of the node's named children.
+//! * [`AllChildren`]: Values of all the node's children, including anonymous nodes.
+//! * [`TreeValue`]: Any node with its kind, text and named children.
+//! * [`KindTagged`]: Internally tagged enum(`#[serde(tag = "$kind")]`) keyed by the node's kind.
+//! * [`MaybeKind`]: `Some` if the node's kind matches the inner type, `None` otherwise.
+//! * [`Presence`]: Value of a struct field with whether the field was present.
+//! * [`Cached`]: Value reused across incremental re-parses by [`from_tree_cached`].
+//! * [`First`], [`Last`]: Value of the first or last node of a struct field with multiple nodes.
+//! * [`Joined`]: Text of all nodes of a struct field, concatenated.
+//! * [`NextSibling`]: Value of the named sibling after the node.
+//! * [`ByChild`]: Value of the node's single named child, e.g. an enum of keyword children.
+//! * [`Flag`]: Presence of the node. In a struct field, `Flag(false)` if the field is empty.
+//!
+//! Use [`describe`] to see what a type expects.
+//!
+//! ## Reserved field names
+//!
+//! * `$span`: Matches the struct node itself instead of its field. Use with [`Span`].
+//! * `$text`: Same as `$span`, but intended for text. This is useful to model a leaf token as a
+//!   struct, since the field captures the whole text of the node regardless of its children:
+//!
+//! ```
+//! # #[cfg(feature = "tree-sitter")] {
+//! // `string` has a `string_content` child, but the field takes the text with quotes.
+//! #[derive(Debug, PartialEq, serde::Deserialize)]
+//! #[serde(rename = "string")]
+//! struct Str {
+//!     #[serde(rename = "$text")]
+//!     literal: String,
+//! }
+//!
+//! #[derive(Debug, PartialEq, serde::Deserialize)]
+//! #[serde(rename = "document")]
+//! struct Document(Str);
+//!
+//! let src = r#""a\nb""#;
+//! let document: Document =
+//!     serde_tree_sitter::from_str(src, tree_sitter_json::language(), true).unwrap();
+//! assert_eq!(document.0.literal, r#""a\nb""#);
+//! # }
+//! ```
+//!
+//! To keep the whole text of a struct node along with its fields, use [`RawText`] for the `$text`
+//! field.
+//!
+//! * `$rest`: Named children that are not claimed by other fields of the struct, in source order.
+//!   This includes children without field names and children of fields the struct doesn't declare.
+//!   Use with `Vec<R>`, e.g. `Vec<TreeValue>` to keep the children the struct doesn't model.
+//!   `#[serde(flatten)]` can't be used for this, since flattened values are buffered as text.
+//!
+//! A `deserialize_with` function can consume its deserializer only once. To use both the text and
+//! the children of a node, deserialize an intermediate struct with `$text` and `$rest` fields in
+//! the function and post-process it. See `examples/custom_children.rs`.
+//!
+//! ## Error locations
+//!
+//! Errors carry the byte range of the node that failed([`DeserializeError::range`]) and, when
+//! known, the kinds of the nodes from the root([`DeserializeError::path`]).
+//!
+//! Nested values are always deserialized through the seeds passed to serde's access traits, so
+//! wrappers that track the path of the failed value(e.g. `serde_path_to_error`) see every level.
+//! Struct fields are visited as a sequence in declaration order, not as a map, so such wrappers
+//! report the position of a field instead of its name: field names are not reported. For example,
+//! the third element of `foo.bar` is reported as `[1][0][2]` when `foo` is the second field of the
+//! root and `bar` is the first field of `foo`. Use [`DeserializeError::path`] for the node kinds.
+//!
+//! Fields are not visited as a map since a missing field must be reported after its value fails:
+//! an absent field whose type needs a node falls back to `#[serde(default)]`, but a map can't
+//! take back the key it has already yielded.
 
 mod access;
+mod cache;
+mod context;
+mod describe;
 mod deserializer;
 mod error;
+mod options;
 mod tsnode;
+mod types;
 
-pub use error::DeserializeError;
+use std::rc::Rc;
 
+pub use cache::{Cached, DeserializeCache};
+pub use describe::describe;
+pub use error::{DeserializeError, SyntaxError, SyntaxErrorKind};
+pub use options::{FromNodeOptions, TextNormalization};
+#[cfg(feature = "tree-sitter")]
+pub use tsnode::TsNodeImpl;
+pub use tsnode::{dump_node, Point, TsNode};
+pub use types::{
+    child_kinds, reassemble, AllChildren, ByChild, ChildKinds, First, Flag, Joined, KindTagged,
+    Last, Located, MaybeKind, NextSibling, NodeId, NodeKind, Positioned, Presence, RawText,
+    SourceSpanned, Span, Spanned, TreeValue,
+};
+
+/// Deserializes the tree's root node. Fails if the tree contains errors.
+#[cfg(feature = "tree-sitter")]
 pub fn from_tree<'d, D: serde::Deserialize<'d>>(
     tree: &'d tree_sitter::Tree,
     src: &'d str,
-    check_error: bool,
 ) -> Result<D, DeserializeError> {
-    from_node(tree.root_node(), src, check_error)
+    from_tree_with_check(tree, src, true)
 }
 
+/// Deserializes the node. Fails if the node contains errors.
+#[cfg(feature = "tree-sitter")]
 pub fn from_node<'de, D: serde::Deserialize<'de>>(
-    node: tree_sitter::Node<'de>,
+    node: tree_sitter::Node,
+    src: &'de str,
+) -> Result<D, DeserializeError> {
+    from_node_with_check(node, src, true)
+}
+
+/// Same as [`from_node`], but the source may not be valid UTF-8.
+///
+/// The text of a node is validated only when it is read as text(strings, numbers, etc.), and
+/// fails with [`DeserializeError::Utf8Error`] if it is not valid UTF-8. Byte types(`&[u8]`) read
+/// the text as is.
+#[cfg(feature = "tree-sitter")]
+pub fn from_node_bytes<'de, D: serde::Deserialize<'de>>(
+    node: tree_sitter::Node,
+    src: &'de [u8],
+) -> Result<D, DeserializeError> {
+    from_node_bytes_with_options(node, src, FromNodeOptions::new())
+}
+
+/// Same as [`from_node_with_options`], but the source may not be valid UTF-8. See
+/// [`from_node_bytes`].
+#[cfg(feature = "tree-sitter")]
+pub fn from_node_bytes_with_options<'de, D: serde::Deserialize<'de>>(
+    node: tree_sitter::Node,
+    src: &'de [u8],
+    options: FromNodeOptions,
+) -> Result<D, DeserializeError> {
+    let ts_node = tsnode::TsNodeImpl::from_bytes(node, src);
+    deserialize_tree_node(std::marker::PhantomData::<D>, node, ts_node, src, options)
+}
+
+/// Parses `src` with `language` and deserializes the root node.
+///
+/// Fails with [`DeserializeError::ParseFailed`] if the parser produced no tree.
+#[cfg(feature = "tree-sitter")]
+pub fn from_str<'de, D: serde::Deserialize<'de>>(
+    src: &'de str,
+    language: tree_sitter::Language,
+    check_error: bool,
+) -> Result<D, DeserializeError> {
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(language)
+        .map_err(DeserializeError::LanguageError)?;
+    let tree = parser
+        .parse(src, None)
+        .ok_or(DeserializeError::ParseFailed)?;
+    from_node_with_check(tree.root_node(), src, check_error)
+}
+
+/// Same as [`from_tree`], but the check for tree-sitter errors can be disabled.
+#[cfg(feature = "tree-sitter")]
+pub fn from_tree_with_check<'d, D: serde::Deserialize<'d>>(
+    tree: &'d tree_sitter::Tree,
+    src: &'d str,
+    check_error: bool,
+) -> Result<D, DeserializeError> {
+    from_node_with_check(tree.root_node(), src, check_error)
+}
+
+/// Same as [`from_node`], but the check for tree-sitter errors can be disabled.
+#[cfg(feature = "tree-sitter")]
+pub fn from_node_with_check<'de, D: serde::Deserialize<'de>>(
+    node: tree_sitter::Node,
+    src: &'de str,
+    check_error: bool,
+) -> Result<D, DeserializeError> {
+    from_node_with_options(node, src, FromNodeOptions::new().check_error(check_error))
+}
+
+#[cfg(feature = "tree-sitter")]
+pub fn from_tree_with_options<'d, D: serde::Deserialize<'d>>(
+    tree: &'d tree_sitter::Tree,
+    src: &'d str,
+    options: FromNodeOptions,
+) -> Result<D, DeserializeError> {
+    from_node_with_options(tree.root_node(), src, options)
+}
+
+#[cfg(feature = "tree-sitter")]
+pub fn from_node_with_options<'de, D: serde::Deserialize<'de>>(
+    node: tree_sitter::Node,
+    src: &'de str,
+    options: FromNodeOptions,
+) -> Result<D, DeserializeError> {
+    from_node_seed_with_options(std::marker::PhantomData::<D>, node, src, options)
+}
+
+/// Same as [`from_node_with_check`], but deserializes the node with a
+/// [`DeserializeSeed`](serde::de::DeserializeSeed).
+///
+/// Derived `Deserialize` impls can't take external state. Use a seed to pass it(e.g. an
+/// interner, or an ID of the source file) to the values being deserialized.
+#[cfg(feature = "tree-sitter")]
+pub fn from_node_seed<'de, S: serde::de::DeserializeSeed<'de>>(
+    seed: S,
+    node: tree_sitter::Node,
     src: &'de str,
     check_error: bool,
+) -> Result<S::Value, DeserializeError> {
+    from_node_seed_with_options(
+        seed,
+        node,
+        src,
+        FromNodeOptions::new().check_error(check_error),
+    )
+}
+
+#[cfg(feature = "tree-sitter")]
+fn from_node_seed_with_options<'de, S: serde::de::DeserializeSeed<'de>>(
+    seed: S,
+    node: tree_sitter::Node,
+    src: &'de str,
+    options: FromNodeOptions,
+) -> Result<S::Value, DeserializeError> {
+    let ts_node = tsnode::TsNodeImpl::new(node, src);
+    deserialize_tree_node(seed, node, ts_node, src, options)
+}
+
+/// Deserializes `ts_node`, the wrapper of `node` over the whole source `src`.
+#[cfg(feature = "tree-sitter")]
+fn deserialize_tree_node<'de, S: serde::de::DeserializeSeed<'de>>(
+    seed: S,
+    node: tree_sitter::Node,
+    ts_node: tsnode::TsNodeImpl<'_, 'de>,
+    src: impl AsRef<[u8]>,
+    options: FromNodeOptions,
+) -> Result<S::Value, DeserializeError> {
+    let skip_error_nodes = options.is_skip_error_nodes();
+    if options.is_check_error() && !skip_error_nodes && node.has_error() {
+        return Err(DeserializeError::TreeSitterError(collect_errors(node, src)));
+    }
+    let ctx = Rc::new(context::Context::new(options));
+    let deserializer = crate::deserializer::NodeDeserializer::enter(
+        ts_node.skip_error_nodes(skip_error_nodes),
+        ctx.clone(),
+    )?;
+    let value = seed.deserialize(deserializer)?;
+    ctx.check_unconsumed()?;
+    Ok(value)
+}
+
+/// Deserializes the nodes in the node's field, without a struct for the node. Fails if the node
+/// contains errors.
+///
+/// The nodes are matched in the same way as a struct field of type `D`. E.g. `String` requires
+/// exact one node, and fails with [`DeserializeError::FieldLength`] if the field is absent or has
+/// multiple nodes.
+///
+/// ```
+/// let src = r#"{"a": 1}"#;
+/// let mut parser = tree_sitter::Parser::new();
+/// parser.set_language(tree_sitter_json::language()).unwrap();
+/// let tree = parser.parse(src, None).unwrap();
+/// // document > object > pair
+/// let pair = tree.root_node().child(0).unwrap().named_child(0).unwrap();
+/// let value: u32 = serde_tree_sitter::field_from_node(pair, src, "value").unwrap();
+/// assert_eq!(value, 1);
+/// ```
+#[cfg(feature = "tree-sitter")]
+pub fn field_from_node<'de, D: serde::Deserialize<'de>>(
+    node: tree_sitter::Node,
+    src: &'de str,
+    field: &'static str,
 ) -> Result<D, DeserializeError> {
-    if check_error && node.has_error() {
-        return Err(DeserializeError::TreeSitterError(collect_errors(node)));
+    if node.has_error() {
+        return Err(DeserializeError::TreeSitterError(collect_errors(node, src)));
+    }
+    let nodes = tsnode::TsNodeImpl::new(node, src)
+        .children_by_field_name(field)
+        .collect();
+    let ctx = Rc::new(context::Context::new(FromNodeOptions::new()));
+    D::deserialize(crate::deserializer::FieldDeserializer::new(
+        field, nodes, ctx,
+    ))
+}
+
+/// Same as [`from_node`], but collects errors instead of failing on the first one.
+///
+/// Values that can't be parsed(numbers, `bool`, `char`) and structs whose node kind doesn't match
+/// are recorded as errors and replaced with placeholders(zero, empty, etc.), so that the rest of
+/// the tree is still checked. Other errors stop deserialization and are returned along with the
+/// errors collected so far. Returns `Ok` only if there is no error.
+#[cfg(feature = "tree-sitter")]
+pub fn from_node_collecting<'de, D: serde::Deserialize<'de>>(
+    node: tree_sitter::Node,
+    src: &'de str,
+) -> Result<D, Vec<DeserializeError>> {
+    if node.has_error() {
+        return Err(vec![DeserializeError::TreeSitterError(collect_errors(
+            node, src,
+        ))]);
+    }
+    let ctx = Rc::new(context::Context::collecting(FromNodeOptions::new()));
+    let result = crate::deserializer::NodeDeserializer::enter(
+        tsnode::TsNodeImpl::new(node, src),
+        ctx.clone(),
+    )
+    .and_then(D::deserialize);
+    let mut errors = ctx.take_errors();
+    match result {
+        Ok(value) if errors.is_empty() => Ok(value),
+        Ok(_) => Err(errors),
+        Err(err) => {
+            errors.push(err);
+            Err(errors)
+        }
     }
-    let deserializer =
-        crate::deserializer::NodeDeserializer::new(tsnode::TsNodeImpl::new(node, src));
+}
+
+/// Deserializes a node of any tree representation that implements [`TsNode`].
+///
+/// Unlike [`from_node`], the node is not checked for tree-sitter errors.
+pub fn from_ts_node<'de, D: serde::Deserialize<'de>, N: TsNode<'de>>(
+    node: N,
+) -> Result<D, DeserializeError> {
+    let deserializer = crate::deserializer::NodeDeserializer::enter(
+        node,
+        Rc::new(context::Context::new(FromNodeOptions::new())),
+    )?;
     D::deserialize(deserializer)
 }
 
-fn collect_errors(node: tree_sitter::Node) -> Vec<tree_sitter::Range> {
-    fn rec(node: tree_sitter::Node, buf: &mut Vec<tree_sitter::Range>) {
-        if node.is_error() {
-            buf.push(node.range());
+/// Deserializes the named children of the tree's root node lazily, one by one.
+///
+/// Unlike `from_tree::<Vec<D>>`, the values are not collected, so a huge list of top-level items
+/// can be processed one at a time. If `check_error` is true and the tree contains errors, yields
+/// only the error.
+#[cfg(feature = "tree-sitter")]
+pub fn iter_from_tree<'d, D: serde::Deserialize<'d> + 'd>(
+    tree: &'d tree_sitter::Tree,
+    src: &'d str,
+    check_error: bool,
+) -> impl Iterator<Item = Result<D, DeserializeError>> + 'd {
+    let node = tree.root_node();
+    let (error, children) = if check_error && node.has_error() {
+        let error = DeserializeError::TreeSitterError(collect_errors(node, src));
+        (Some(error), None)
+    } else {
+        let children = tsnode::TsNodeImpl::new(node, src).into_named_children();
+        (None, Some(children))
+    };
+    let ctx = Rc::new(context::Context::new(FromNodeOptions::new()));
+    error
+        .map(Err)
+        .into_iter()
+        .chain(children.into_iter().flatten().map(move |child| {
+            D::deserialize(crate::deserializer::NodeDeserializer::enter(
+                child,
+                ctx.clone(),
+            )?)
+        }))
+}
+
+/// Same as [`from_tree`], but also returns kinds of the nodes entered
+/// during deserialization, in order.
+///
+/// This is useful to verify that types are matched against the expected grammar structure. The
+/// kinds are returned even if deserialization failed.
+#[cfg(feature = "tree-sitter")]
+pub fn from_tree_traced<'d, D: serde::Deserialize<'d>>(
+    tree: &'d tree_sitter::Tree,
+    src: &'d str,
+) -> (Result<D, DeserializeError>, Vec<String>) {
+    let node = tree.root_node();
+    if node.has_error() {
+        return (
+            Err(DeserializeError::TreeSitterError(collect_errors(node, src))),
+            vec![],
+        );
+    }
+    let ctx = Rc::new(context::Context::traced(FromNodeOptions::new()));
+    let result = crate::deserializer::NodeDeserializer::enter(
+        tsnode::TsNodeImpl::new(node, src),
+        ctx.clone(),
+    )
+    .and_then(D::deserialize);
+    (result, ctx.take_trace())
+}
+
+/// Same as [`from_tree`], but reuses [`Cached`] values from `cache`.
+///
+/// A cached value is reused if its node is shared with the previously deserialized tree and is
+/// at the same position. Use this with incremental parsing(`Parser::parse(src, Some(&old_tree))`),
+/// and keep the previous tree alive until the new tree is parsed.
+#[cfg(feature = "tree-sitter")]
+pub fn from_tree_cached<'d, D: serde::Deserialize<'d>>(
+    tree: &'d tree_sitter::Tree,
+    src: &'d str,
+    cache: &mut DeserializeCache,
+) -> Result<D, DeserializeError> {
+    cache::with_cache(cache, || from_tree(tree, src))
+}
+
+/// Runs `query` against the tree and deserializes each node captured as `capture_name`.
+///
+/// Fails on the first node that can't be deserialized. Each captured node is checked for
+/// tree-sitter errors(as `check_error = true`), but errors outside of the captured nodes are
+/// ignored.
+#[cfg(feature = "tree-sitter")]
+pub fn deserialize_query<'de, D: serde::Deserialize<'de>>(
+    tree: &'de tree_sitter::Tree,
+    src: &'de str,
+    query: &tree_sitter::Query,
+    capture_name: &str,
+) -> Result<Vec<D>, DeserializeError> {
+    let Some(index) = query.capture_index_for_name(capture_name) else {
+        return Err(DeserializeError::UnknownCapture(capture_name.to_owned()));
+    };
+    let mut cursor = tree_sitter::QueryCursor::new();
+    cursor
+        .matches(query, tree.root_node(), src.as_bytes())
+        .flat_map(|m| m.nodes_for_capture_index(index).collect::<Vec<_>>())
+        .map(|node| from_node(node, src))
+        .collect()
+}
+
+/// Same as [`deserialize_query`], but collects errors instead of failing on the first one.
+///
+/// Returns the values of the captured nodes that are deserialized successfully, and the errors of
+/// the others, both in the order of the captures. An unknown `capture_name` is reported as the
+/// only error.
+#[cfg(feature = "tree-sitter")]
+pub fn deserialize_query_collecting<'de, D: serde::Deserialize<'de>>(
+    tree: &'de tree_sitter::Tree,
+    src: &'de str,
+    query: &tree_sitter::Query,
+    capture_name: &str,
+) -> (Vec<D>, Vec<DeserializeError>) {
+    let Some(index) = query.capture_index_for_name(capture_name) else {
+        return (
+            vec![],
+            vec![DeserializeError::UnknownCapture(capture_name.to_owned())],
+        );
+    };
+    let mut values = Vec::new();
+    let mut errors = Vec::new();
+    let mut cursor = tree_sitter::QueryCursor::new();
+    for m in cursor.matches(query, tree.root_node(), src.as_bytes()) {
+        for node in m.nodes_for_capture_index(index) {
+            match from_node(node, src) {
+                Ok(value) => values.push(value),
+                Err(err) => errors.push(err),
+            }
+        }
+    }
+    (values, errors)
+}
+
+/// Deserializes the captures of a query match into a struct, whose fields are named after the
+/// captures.
+///
+/// A field takes all nodes captured with its name, so use `Vec<T>` for a capture that may match
+/// more than once, and `Option<T>` for an optional one. Captures without
+/// corresponding field are ignored. Each captured node is checked for tree-sitter errors.
+#[cfg(feature = "tree-sitter")]
+pub fn from_query_match<'de, D: serde::Deserialize<'de>>(
+    m: &tree_sitter::QueryMatch,
+    query: &tree_sitter::Query,
+    src: &'de str,
+) -> Result<D, DeserializeError> {
+    if let Some(c) = m.captures.iter().find(|c| c.node.has_error()) {
+        return Err(DeserializeError::TreeSitterError(collect_errors(
+            c.node, src,
+        )));
+    }
+    let names = query.capture_names();
+    let captures = m.captures.iter().map(|c| {
+        (
+            names[c.index as usize].as_str(),
+            tsnode::TsNodeImpl::new(c.node, src),
+        )
+    });
+    D::deserialize(crate::deserializer::CapturesDeserializer::new(
+        captures,
+        Rc::new(context::Context::new(FromNodeOptions::new())),
+    ))
+}
+
+/// Deserializes `node` by dispatching on the kind of its first named child.
+///
+/// The constructor paired with the child's kind in `table` is called with the child node. This is
+/// an explicit alternative to deserializing into an enum when the automatic mapping rules don't
+/// fit.
+#[cfg(feature = "tree-sitter")]
+pub fn deserialize_by_child_kind<'de, D>(
+    node: tree_sitter::Node<'de>,
+    src: &'de str,
+    table: &[(&str, NodeConstructor<'de, D>)],
+) -> Result<D, DeserializeError> {
+    let Some(child) = node.named_child(0) else {
+        return Err(DeserializeError::child_length(1, 0));
+    };
+    match table.iter().find(|(kind, _)| *kind == child.kind()) {
+        Some((_, constructor)) => constructor(child, src),
+        None => Err(DeserializeError::node_type(
+            table
+                .iter()
+                .map(|(kind, _)| *kind)
+                .collect::<Vec<_>>()
+                .join(" | "),
+            child.kind(),
+        )),
+    }
+}
+
+/// Tries `alternatives` in order against `node`, and returns the first success.
+///
+/// If all alternatives fail, returns
+/// [`DeserializeError::AllAlternativesFailed`] with the errors of each alternative.
+#[cfg(feature = "tree-sitter")]
+pub fn try_all<'de, D>(
+    node: tree_sitter::Node<'de>,
+    src: &'de str,
+    alternatives: &[NodeConstructor<'de, D>],
+) -> Result<D, DeserializeError> {
+    let mut errors = Vec::with_capacity(alternatives.len());
+    for alternative in alternatives {
+        match alternative(node, src) {
+            Ok(value) => return Ok(value),
+            Err(err) => errors.push(err),
+        }
+    }
+    Err(DeserializeError::AllAlternativesFailed(errors))
+}
+
+/// Function that builds a value from a node, used by [`deserialize_by_child_kind`] and
+/// [`try_all`].
+#[cfg(feature = "tree-sitter")]
+pub type NodeConstructor<'de, D> =
+    fn(tree_sitter::Node<'de>, &'de str) -> Result<D, DeserializeError>;
+
+/// `ERROR` and `MISSING` nodes in the node and its descendants, in source order.
+///
+/// This is what [`DeserializeError::TreeSitterError`] reports. Use this to report all syntax
+/// errors before deserializing, independently of how the deserialization goes:
+///
+/// ```
+/// use serde_tree_sitter::{collect_errors, from_tree_with_options, FromNodeOptions};
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "document")]
+/// struct Document(Vec<Vec<u32>>);
+///
+/// let src = "[1, 2 3]";
+/// let mut parser = tree_sitter::Parser::new();
+/// parser.set_language(tree_sitter_json::language()).unwrap();
+/// let tree = parser.parse(src, None).unwrap();
+/// let syntax_errors = collect_errors(tree.root_node(), src);
+/// for error in &syntax_errors {
+///     eprintln!("{error}");
+/// }
+/// assert_eq!(syntax_errors.len(), 1);
+/// let options = FromNodeOptions::new().skip_error_nodes(!syntax_errors.is_empty());
+/// let document: Document = from_tree_with_options(&tree, src, options).unwrap();
+/// assert_eq!(document, Document(vec![vec![1, 2]]));
+/// ```
+#[cfg(feature = "tree-sitter")]
+pub fn collect_errors(node: tree_sitter::Node, src: impl AsRef<[u8]>) -> Vec<SyntaxError> {
+    fn rec(node: tree_sitter::Node, src: &[u8], buf: &mut Vec<SyntaxError>) {
+        if node.is_error() || node.is_missing() {
+            buf.push(SyntaxError::new(node, src));
         }
         if node.has_error() {
             for c in node.children(&mut node.walk()) {
-                rec(c, buf);
+                rec(c, src, buf);
             }
         }
     }
     let mut buf = Vec::new();
-    rec(node, &mut buf);
+    rec(node, src.as_ref(), &mut buf);
     buf
 }
 
 #[cfg(test)]
 mod test {
-    use self::tsnode::TsNode;
-
     use super::*;
 
     struct DummyNode {
         kind: &'static str,
         src: &'static str,
         named_children: Vec<(Option<&'static str>, DummyNode)>,
+        position: (Point, Point),
     }
     impl DummyNode {
         fn new(
@@ -107,44 +727,94 @@ mod test {
                 kind,
                 src,
                 named_children,
+                position: (Point::new(0, 0), Point::new(0, src.len())),
             }
         }
+        fn at(mut self, start: (usize, usize), end: (usize, usize)) -> DummyNode {
+            self.position = (Point::new(start.0, start.1), Point::new(end.0, end.1));
+            self
+        }
     }
-    impl std::fmt::Debug for &DummyNode {
+    impl std::fmt::Debug for DummyNode {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             f.debug_struct("DummyNode")
                 .field("kind", &self.kind)
-                .field("named_child_count", &self.named_child_count())
-                .field(
-                    "children",
-                    &self
-                        .named_children
-                        .iter()
-                        .map(|(f, n)| (f, n))
-                        .collect::<Vec<_>>(),
-                )
+                .field("named_child_count", &self.named_children.len())
+                .field("children", &self.named_children)
                 .finish_non_exhaustive()
         }
     }
-    impl<'de> TsNode<'de> for &DummyNode {
+
+    /// A `DummyNode` with the link to its parent, to find the siblings.
+    #[derive(Clone, Copy)]
+    struct DummyRef<'a> {
+        node: &'a DummyNode,
+        parent: Option<(&'a DummyNode, usize)>,
+    }
+    impl<'a> DummyRef<'a> {
+        fn root(node: &'a DummyNode) -> DummyRef<'a> {
+            DummyRef { node, parent: None }
+        }
+        fn child(&self, index: usize) -> DummyRef<'a> {
+            DummyRef {
+                node: &self.node.named_children[index].1,
+                parent: Some((self.node, index)),
+            }
+        }
+        fn sibling(&self, index: Option<usize>) -> Option<DummyRef<'a>> {
+            let (parent, _) = self.parent?;
+            let index = index.filter(|i| *i < parent.named_children.len())?;
+            Some(DummyRef::root(parent).child(index))
+        }
+    }
+    impl std::ops::Deref for DummyRef<'_> {
+        type Target = DummyNode;
+
+        fn deref(&self) -> &DummyNode {
+            self.node
+        }
+    }
+    impl std::fmt::Debug for DummyRef<'_> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            self.node.fmt(f)
+        }
+    }
+    impl<'de> TsNode<'de> for DummyRef<'de> {
         fn named_child(&self, index: usize) -> Option<Self> {
-            self.named_children.get(index).map(|x| &x.1)
+            (index < self.named_children.len()).then(|| self.child(index))
         }
 
         fn named_child_count(&self) -> usize {
             self.named_children.len()
         }
 
-        fn named_children(&self) -> impl ExactSizeIterator<Item = Self> {
-            self.named_children.iter().map(|(_, n)| n)
+        fn named_children(&self) -> impl Iterator<Item = Self> {
+            (0..self.named_children.len()).map(|i| self.child(i))
         }
 
-        fn children_by_field_name(&self, name: &str) -> impl ExactSizeIterator<Item = Self> {
-            self.named_children
-                .iter()
-                .filter_map(|(f, n)| f.filter(|f| f == &name).map(|_| n))
-                .collect::<Vec<_>>()
-                .into_iter()
+        fn child_count(&self) -> usize {
+            self.named_child_count()
+        }
+
+        fn children(&self) -> impl Iterator<Item = Self> {
+            self.named_children()
+        }
+
+        fn children_by_field_name(&self, name: &str) -> impl Iterator<Item = Self> {
+            let name = name.to_owned();
+            (0..self.named_children.len())
+                .filter(move |i| self.named_children[*i].0 == Some(name.as_str()))
+                .map(|i| self.child(i))
+        }
+
+        fn field_names(&self) -> Vec<&'static str> {
+            let mut names = Vec::new();
+            for name in self.named_children.iter().filter_map(|(f, _)| *f) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+            names
         }
 
         fn kind(&self) -> &'static str {
@@ -154,6 +824,34 @@ mod test {
         fn src(&self) -> &'de str {
             self.src
         }
+
+        fn byte_range(&self) -> std::ops::Range<usize> {
+            0..self.src.len()
+        }
+
+        fn start_position(&self) -> Point {
+            self.position.0
+        }
+
+        fn end_position(&self) -> Point {
+            self.position.1
+        }
+
+        fn id(&self) -> usize {
+            self.node as *const DummyNode as usize
+        }
+
+        fn is_error(&self) -> bool {
+            self.kind == "ERROR"
+        }
+
+        fn next_named_sibling(&self) -> Option<Self> {
+            self.sibling(self.parent.map(|(_, i)| i + 1))
+        }
+
+        fn prev_named_sibling(&self) -> Option<Self> {
+            self.sibling(self.parent.and_then(|(_, i)| i.checked_sub(1)))
+        }
     }
 
     #[ctor::ctor]
@@ -165,7 +863,17 @@ mod test {
     use serde::Deserialize;
 
     fn deserialize<'de, D: Deserialize<'de>>(node: &'de DummyNode) -> Result<D, DeserializeError> {
-        D::deserialize(crate::deserializer::NodeDeserializer::new(node))
+        deserialize_with_options(node, FromNodeOptions::new())
+    }
+
+    fn deserialize_with_options<'de, D: Deserialize<'de>>(
+        node: &'de DummyNode,
+        options: FromNodeOptions,
+    ) -> Result<D, DeserializeError> {
+        D::deserialize(crate::deserializer::NodeDeserializer::enter(
+            DummyRef::root(node),
+            Rc::new(context::Context::new(options)),
+        )?)
     }
 
     macro_rules! make_node {
@@ -196,15 +904,70 @@ mod test {
             assert_eq!(deserialize::<$t>(&make_node!($($node)+)).unwrap(), $expected);
         };
     }
+    // Byte ranges of errors are checked separately in `test_error_range`.
     macro_rules! assert_err {
         ($t:ty, ($($node:tt)+), $expected:expr) => {
-            assert_eq!(deserialize::<$t>(&make_node!($($node)+)).unwrap_err(), $expected);
+            assert_eq!(
+                deserialize::<$t>(&make_node!($($node)+))
+                    .unwrap_err()
+                    .without_range(),
+                &$expected
+            );
         };
     }
 
+    #[test]
+    fn test_named_child_by_kind() {
+        let node = make_node!(root (a "1") (b "2") (a "3"));
+        let node = DummyRef::root(&node);
+        assert_eq!(node.named_child_by_kind("a").map(|c| c.src()), Some("1"));
+        assert_eq!(node.named_child_by_kind("b").map(|c| c.src()), Some("2"));
+        assert!(node.named_child_by_kind("c").is_none());
+
+        #[cfg(feature = "tree-sitter")]
+        {
+            let mut parser = tree_sitter::Parser::new();
+            parser.set_language(tree_sitter_json::language()).unwrap();
+            let src = r#"[1, "a", 2]"#;
+            let tree = parser.parse(src, None).unwrap();
+            let array = tsnode::TsNodeImpl::new(tree.root_node(), src)
+                .named_child(0)
+                .unwrap();
+            assert_eq!(
+                array.named_child_by_kind("number").map(|c| c.src()),
+                Some("1")
+            );
+            assert_eq!(
+                array.named_child_by_kind("string").map(|c| c.src()),
+                Some(r#""a""#)
+            );
+            assert!(array.named_child_by_kind("null").is_none());
+        }
+    }
+
+    #[test]
+    fn test_from_ts_node() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root<'a> {
+            a: &'a str,
+            b: Vec<u32>,
+        }
+        let node = make_node!(root a: (child "x") b: (child "1") b: (child "2"));
+        assert_eq!(
+            from_ts_node::<Root, _>(DummyRef::root(&node)).unwrap(),
+            Root {
+                a: "x",
+                b: vec![1, 2]
+            }
+        );
+    }
+
     #[test]
     fn test_unit_ok() {
         assert_ok!((), (root), ());
+        // Children are ignored, unlike empty tuples
+        assert_ok!((), (root (child "1")), ());
     }
 
     macro_rules! define_test_simple_ok {
@@ -223,19 +986,23 @@ mod test {
                 assert_err!(
                     $t,
                     (root "invalid_value"),
-                    DeserializeError::$err("invalid_value".parse::<$t>().unwrap_err())
+                    DeserializeError::$err(
+                        "invalid_value".parse::<$t>().unwrap_err(),
+                        "invalid_value",
+                        "root"
+                    )
                 );
             }
         };
     }
     macro_rules! define_test_int {
         ($name:ident, $t:ty, $repr:literal, $expected:expr) => {
-            define_test_simple!($name, $t, $repr, $expected, ParseIntError);
+            define_test_simple!($name, $t, $repr, $expected, parse_int);
         };
     }
     macro_rules! define_test_float {
         ($name:ident, $t:ty, $repr:literal, $expected:expr) => {
-            define_test_simple!($name, $t, $repr, $expected, ParseFloatError);
+            define_test_simple!($name, $t, $repr, $expected, parse_float);
         };
     }
 
@@ -249,7 +1016,7 @@ mod test {
     define_test_int!(test_u64_ok, u64, "123", 123);
     define_test_float!(test_f32_ok, f32, "1234.5", 1234.5);
     define_test_float!(test_f64_ok, f64, "1234.5", 1234.5);
-    define_test_simple!(test_bool_ok, bool, "true", true, ParseBoolError);
+    define_test_simple!(test_bool_ok, bool, "true", true, parse_bool);
 
     define_test_simple_ok!(test_string_ok, String, "abc", "abc".to_owned());
     define_test_simple_ok!(test_str_ok, &str, "abc", "abc");
@@ -262,6 +1029,8 @@ mod test {
         struct Root;
 
         assert_ok!(Root, (root), Root);
+        // Children are ignored, unlike `struct Root()`
+        assert_ok!(Root, (root (child "1")), Root);
         assert_err!(
             Root,
             (not_root),
@@ -313,6 +1082,63 @@ mod test {
             (root (child "123") (child "456") (child "789")),
             DeserializeError::child_length(2, 3)
         );
+
+        // Field names are ignored
+        assert_ok!(
+            Root,
+            (root b: (child "123") (child "456")),
+            Root(123, 456)
+        );
+        assert_ok!(
+            Root,
+            (root b: (child "123") a: (child "456")),
+            Root(123, 456)
+        );
+    }
+
+    #[test]
+    fn test_tuple_struct_rest() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "call")]
+        struct Call(String, Vec<u32>);
+
+        assert_ok!(Call, (call (name "f")), Call("f".into(), vec![]));
+        assert_ok!(
+            Call,
+            (call (name "f") (arg "1")),
+            Call("f".into(), vec![1])
+        );
+        assert_ok!(
+            Call,
+            (call (name "f") (arg "1") (arg "2") (arg "3")),
+            Call("f".into(), vec![1, 2, 3])
+        );
+        assert_err!(Call, (call), DeserializeError::child_length(2, 0));
+
+        assert_ok!(
+            (u32, u32, Vec<u32>),
+            (root (child "1") (child "2") (child "3") (child "4")),
+            (1, 2, vec![3, 4])
+        );
+        assert_err!(
+            (u32, u32, Vec<u32>),
+            (root (child "1")),
+            DeserializeError::child_length(3, 1)
+        );
+
+        // A list node as the last child needs a newtype struct
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "argument_list")]
+        struct ArgumentList(Vec<u32>);
+        assert_ok!(
+            (String, ArgumentList),
+            (call (name "f") (argument_list (arg "1") (arg "2"))),
+            ("f".into(), ArgumentList(vec![1, 2]))
+        );
+        assert!(deserialize::<(String, Vec<u32>)>(&make_node!(
+            call (name "f") (argument_list (arg "1") (arg "2"))
+        ))
+        .is_err());
     }
 
     #[test]
@@ -401,6 +1227,17 @@ mod test {
             (root (child "123") (child "456") (child "789")),
             DeserializeError::child_length(2, 3)
         );
+
+        // The last sequence takes the rest
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Rest((u32, Vec<u32>));
+        assert_ok!(
+            Rest,
+            (root (child "1") (child "2") (child "3")),
+            Rest((1, vec![2, 3]))
+        );
+        assert_ok!(Rest, (root (child "1")), Rest((1, vec![])));
     }
 
     #[test]
@@ -473,7 +1310,7 @@ mod test {
         assert_err!(
             Root,
             (root a: (child "xxx") b: (child "abc")),
-            DeserializeError::ParseIntError("xxx".parse::<u64>().unwrap_err())
+            DeserializeError::parse_int("xxx".parse::<u64>().unwrap_err(), "xxx", "child")
         );
         assert_err!(
             Root,
@@ -483,48 +1320,531 @@ mod test {
     }
 
     #[test]
-    fn test_struct_tuple() {
+    fn test_struct_node_type() {
         #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
         #[serde(rename = "root")]
         struct Root {
-            a: (u32, u32),
+            value: Option<Str>,
         }
 
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "string")]
+        struct Str(String);
+
         assert_ok!(
             Root,
-            (root
-                a: (child "123")
-                (child "999")
-                a: (child "456")),
-            Root { a: (123, 456) }
+            (root value: (string "abc")),
+            Root { value: Some(Str("abc".into())) }
         );
-        assert_err!(
-            Root,
-            (root
-                a: (child "123")
-                (child "999")),
-            DeserializeError::field_length("a", 2, 1)
+        let err = deserialize::<Root>(&make_node!(root value: (number "123"))).unwrap_err();
+        assert_eq!(
+            err,
+            DeserializeError::field_node_type("value", "string", "number")
+                .at(0..3)
+                .with_path(vec!["root", "number"])
+        );
+        assert_eq!(
+            err.to_string(),
+            "in field `value`: node type expected `string`, actual `number` at bytes 0..3 in `root > number`"
         );
     }
 
     #[test]
-    fn test_struct_vec() {
+    fn test_struct_ignored_child() {
         #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
         #[serde(rename = "root")]
         struct Root {
-            a: Vec<u32>,
+            a: i32,
         }
 
-        assert_ok!(
-            Root,
-            (root "999"),
-            Root { a: vec![]}
+        let ignored = Rc::new(std::cell::RefCell::new(Vec::new()));
+        let options = FromNodeOptions::new().on_ignored_child({
+            let ignored = ignored.clone();
+            move |kind, range| ignored.borrow_mut().push((kind.to_owned(), range))
+        });
+        assert_eq!(
+            deserialize_with_options::<Root>(
+                &make_node!(root a: (number "1") b: (string "xy") (comment "abc")),
+                options
+            )
+            .unwrap(),
+            Root { a: 1 }
         );
-        assert_ok!(
-            Root,
-            (root "999"
-                a: (child "123")),
-            Root { a: vec![123]}
+        assert_eq!(
+            *ignored.borrow(),
+            vec![("string".to_owned(), 0..2), ("comment".to_owned(), 0..3)]
+        );
+    }
+
+    #[test]
+    fn test_struct_text() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "identifier")]
+        struct Identifier {
+            #[serde(rename = "$text")]
+            name: String,
+        }
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "number")]
+        struct Number<'a> {
+            #[serde(rename = "$text")]
+            text: &'a str,
+            sign: Option<&'a str>,
+        }
+
+        assert_ok!(
+            Identifier,
+            (identifier "foo"),
+            Identifier { name: "foo".into() }
+        );
+        assert_ok!(
+            Identifier,
+            (identifier "foo" (part "f") (part "oo")),
+            Identifier { name: "foo".into() }
+        );
+        assert_err!(
+            Identifier,
+            (string "foo"),
+            DeserializeError::node_type("identifier", "string")
+        );
+        assert_ok!(
+            Number,
+            (number "-12" sign: (sign "-")),
+            Number { text: "-12", sign: Some("-") }
+        );
+    }
+
+    #[test]
+    fn test_raw_text() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "pair")]
+        struct Pair<'a> {
+            key: &'a str,
+            value: u32,
+            #[serde(rename = "$text", borrow)]
+            raw: RawText<'a>,
+        }
+
+        assert_ok!(
+            Pair,
+            (pair "a: 1" key: (key "a") value: (int "1")),
+            Pair {
+                key: "a",
+                value: 1,
+                raw: RawText("a: 1")
+            }
+        );
+        assert_ok!(RawText, (string " x "), RawText(" x "));
+    }
+
+    #[test]
+    fn test_deserialize_any() {
+        // What `deserialize_any` offers
+        #[derive(PartialEq, Debug)]
+        enum Any {
+            Bool(bool),
+            Int(i64),
+            UInt(u64),
+            Float(f64),
+            Str(String),
+            Seq(Vec<Any>),
+        }
+        impl<'de> Deserialize<'de> for Any {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct Visitor;
+                impl<'de> serde::de::Visitor<'de> for Visitor {
+                    type Value = Any;
+                    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        f.write_str("any value")
+                    }
+                    fn visit_bool<E: serde::de::Error>(self, v: bool) -> Result<Any, E> {
+                        Ok(Any::Bool(v))
+                    }
+                    fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Any, E> {
+                        Ok(Any::Int(v))
+                    }
+                    fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Any, E> {
+                        Ok(Any::UInt(v))
+                    }
+                    fn visit_f64<E: serde::de::Error>(self, v: f64) -> Result<Any, E> {
+                        Ok(Any::Float(v))
+                    }
+                    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Any, E> {
+                        Ok(Any::Str(v.to_owned()))
+                    }
+                    fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                        self,
+                        mut seq: A,
+                    ) -> Result<Any, A::Error> {
+                        let mut values = vec![];
+                        while let Some(value) = seq.next_element()? {
+                            values.push(value);
+                        }
+                        Ok(Any::Seq(values))
+                    }
+                }
+                deserializer.deserialize_any(Visitor)
+            }
+        }
+
+        // A node without named children is its text, parsed as the first primitive type it fits
+        assert_ok!(Any, (true "true"), Any::Bool(true));
+        assert_ok!(Any, (number "-1"), Any::Int(-1));
+        assert_ok!(Any, (number "18446744073709551615"), Any::UInt(u64::MAX));
+        assert_ok!(Any, (number "1.5"), Any::Float(1.5));
+        assert_ok!(Any, (number "1e3"), Any::Float(1000.0));
+        assert_ok!(Any, (identifier "nan"), Any::Str("nan".into()));
+        assert_ok!(Any, (identifier "inf"), Any::Str("inf".into()));
+        assert_ok!(Any, (array "[]"), Any::Str("[]".into()));
+        assert_ok!(
+            Any,
+            (array "[1, [2]]" (number "1") (array "[2]" (number "2"))),
+            Any::Seq(vec![Any::Int(1), Any::Seq(vec![Any::Int(2)])])
+        );
+        // Map values are not parsed, since serde buffers them for flattened fields
+        assert_ok!(
+            std::collections::BTreeMap<String, Any>,
+            (root a: (number "1") b: (array "[1]" (number "1"))),
+            [
+                ("a".into(), Any::Str("1".into())),
+                ("b".into(), Any::Seq(vec![Any::Str("1".into())]))
+            ]
+            .into()
+        );
+        // `IgnoredAny` doesn't read the node
+        assert_ok!(serde::de::IgnoredAny, (array "[]"), serde::de::IgnoredAny);
+    }
+
+    #[test]
+    fn test_struct_flatten() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        struct Header {
+            name: String,
+            // Flattened values are buffered as text, so only string-like types are supported.
+            version: String,
+        }
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "package")]
+        struct Package {
+            #[serde(flatten)]
+            header: Header,
+            license: Option<String>,
+        }
+        assert_ok!(
+            Package,
+            (package name: (string "foo") version: (number "2") license: (string "MIT")),
+            Package {
+                header: Header {
+                    name: "foo".into(),
+                    version: "2".into(),
+                },
+                license: Some("MIT".into()),
+            }
+        );
+
+        assert_ok!(
+            std::collections::BTreeMap<String, String>,
+            (package name: (string "foo") (comment "c") version: (number "2")),
+            [("name".into(), "foo".into()), ("version".into(), "2".into())].into()
+        );
+        assert_err!(
+            std::collections::BTreeMap<String, String>,
+            (package name: (string "a") name: (string "b")),
+            DeserializeError::field_length("name", 1, 2)
+        );
+    }
+
+    #[test]
+    fn test_struct_rest() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root<'a> {
+            name: &'a str,
+            #[serde(rename = "$rest")]
+            rest: Vec<&'a str>,
+        }
+        assert_ok!(
+            Root,
+            (root (comment "c") name: (ident "x") value: (int "1") (comment "d")),
+            Root {
+                name: "x",
+                rest: vec!["c", "1", "d"]
+            }
+        );
+        assert_ok!(
+            Root,
+            (root name: (ident "x")),
+            Root {
+                name: "x",
+                rest: vec![]
+            }
+        );
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Values<'a> {
+            name: &'a str,
+            #[serde(rename = "$rest")]
+            rest: Vec<TreeValue>,
+        }
+        assert_ok!(
+            Values,
+            (root name: (ident "x") value: (int "1")),
+            Values {
+                name: "x",
+                rest: vec![TreeValue {
+                    kind: "int".into(),
+                    text: "1".into(),
+                    children: vec![],
+                }]
+            }
+        );
+
+        // Children in the rest are not ignored.
+        let ignored = Rc::new(std::cell::RefCell::new(vec![]));
+        let options = FromNodeOptions::new().on_ignored_child({
+            let ignored = ignored.clone();
+            move |kind, _| ignored.borrow_mut().push(kind.to_owned())
+        });
+        let node = make_node!(root name: (ident "x") (comment "c"));
+        deserialize_with_options::<Root>(&node, options).unwrap();
+        assert!(ignored.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_untagged_enum() {
+        #[derive(serde::Deserialize, PartialEq, Debug)]
+        #[serde(untagged)]
+        enum Value {
+            Bool(bool),
+            Int(i64),
+            Float(f64),
+            Leaf(String),
+            List(Vec<Value>),
+        }
+
+        assert_ok!(Value, (number "1"), Value::Int(1));
+        assert_ok!(Value, (number "-1.5"), Value::Float(-1.5));
+        assert_ok!(Value, (true "true"), Value::Bool(true));
+        assert_ok!(Value, (string "a"), Value::Leaf("a".into()));
+        assert_ok!(
+            Value,
+            (array (number "1") (string "a")),
+            Value::List(vec![Value::Int(1), Value::Leaf("a".into())])
+        );
+        assert_ok!(
+            Value,
+            (array (array (number "1")) (array (number "2"))),
+            Value::List(vec![
+                Value::List(vec![Value::Int(1)]),
+                Value::List(vec![Value::Int(2)]),
+            ])
+        );
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(untagged)]
+        enum Number {
+            Int(i64),
+        }
+        assert_ok!(Number, (number "1"), Number::Int(1));
+        assert_eq!(
+            deserialize_with_options::<Number>(
+                &make_node!(number "0x10"),
+                FromNodeOptions::new().radix_prefixes(true)
+            )
+            .unwrap(),
+            Number::Int(16)
+        );
+
+        // Text that parses as a primitive is not a string
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(untagged)]
+        enum Text {
+            Leaf(String),
+        }
+        assert!(deserialize::<Text>(&make_node!(number "1")).is_err());
+    }
+
+    #[test]
+    fn test_enum_other() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename_all = "snake_case")]
+        enum Expr {
+            Number(u32),
+            #[serde(rename = "$other")]
+            Other(String),
+        }
+        assert_ok!(Expr, (number "1"), Expr::Number(1));
+        assert_ok!(Expr, (string "a"), Expr::Other("string".into()));
+        assert_ok!(
+            Vec<Expr>,
+            (root (number "1") (call "f()")),
+            vec![Expr::Number(1), Expr::Other("call".into())]
+        );
+        assert_eq!(
+            describe::<Expr>(),
+            "one of kinds `number`, or any other kind"
+        );
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename_all = "snake_case")]
+        enum Unit {
+            Number(u32),
+            #[serde(other)]
+            Other,
+        }
+        assert_ok!(Unit, (number "1"), Unit::Number(1));
+        assert_ok!(Unit, (string "a"), Unit::Other);
+    }
+
+    #[test]
+    fn test_kind_tagged() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(tag = "$kind", rename_all = "snake_case")]
+        enum Value {
+            Null,
+            Number {
+                #[serde(rename = "$text")]
+                text: String,
+            },
+            Pair {
+                key: String,
+                value: String,
+            },
+        }
+
+        assert_ok!(KindTagged<Value>, (null "null"), KindTagged(Value::Null));
+        assert_ok!(
+            KindTagged<Value>,
+            (number "1"),
+            KindTagged(Value::Number { text: "1".into() })
+        );
+        assert_ok!(
+            KindTagged<Value>,
+            (pair key: (string "a") value: (number "1")),
+            KindTagged(Value::Pair {
+                key: "a".into(),
+                value: "1".into()
+            })
+        );
+        assert_err!(
+            KindTagged<Value>,
+            (array "[]"),
+            DeserializeError::UnknownVariant {
+                kind: "array".into(),
+                expected: vec!["null", "number", "pair"]
+            }
+        );
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "array")]
+        struct Array(Vec<KindTagged<Value>>);
+        assert_ok!(
+            Array,
+            (array (null "null") (number "2")),
+            Array(vec![
+                KindTagged(Value::Null),
+                KindTagged(Value::Number { text: "2".into() })
+            ])
+        );
+    }
+
+    #[test]
+    fn test_struct_tuple() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root {
+            a: (u32, u32),
+        }
+
+        assert_ok!(
+            Root,
+            (root
+                a: (child "123")
+                (child "999")
+                a: (child "456")),
+            Root { a: (123, 456) }
+        );
+        assert_err!(
+            Root,
+            (root
+                a: (child "123")
+                (child "999")),
+            DeserializeError::field_length("a", 2, 1)
+        );
+        assert_err!(
+            Root,
+            (root a: (child "1") a: (child "2") a: (child "3")),
+            DeserializeError::field_length("a", 2, 3)
+        );
+
+        // The last sequence takes the rest
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Rest {
+            a: (u32, Vec<u32>),
+        }
+        assert_ok!(
+            Rest,
+            (root a: (child "1") (child "999") a: (child "2") a: (child "3")),
+            Rest { a: (1, vec![2, 3]) }
+        );
+        assert_ok!(
+            Rest,
+            (root a: (child "1")),
+            Rest { a: (1, vec![]) }
+        );
+        assert_err!(Rest, (root), DeserializeError::field_length("a", 2, 0));
+    }
+
+    #[test]
+    fn test_struct_vec() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root {
+            a: Vec<u32>,
+        }
+
+        assert_ok!(
+            Root,
+            (root "999"),
+            Root { a: vec![]}
+        );
+        assert_ok!(
+            Root,
+            (root "999"
+                a: (child "123")),
+            Root { a: vec![123]}
+        );
+        assert_ok!(
+            Root,
+            (root "999"
+                a: (child "123")
+                (child "456")
+                a: (child "789")),
+            Root { a: vec![123, 789]}
+        );
+
+        // Other seq-like containers take the seq path too
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root2 {
+            a: std::collections::VecDeque<u32>,
+            b: std::collections::BTreeSet<u32>,
+        }
+        assert_ok!(
+            Root2,
+            (root),
+            Root2 {
+                a: Default::default(),
+                b: Default::default()
+            }
+        );
+        assert_ok!(
+            Root2,
+            (root a: (child "1") b: (child "3") b: (child "2")),
+            Root2 { a: [1].into(), b: [2, 3].into() }
         );
     }
 
@@ -551,6 +1871,81 @@ mod test {
             (root "123" a: (child "456") a: (child "789")),
             DeserializeError::field_length("a", 1, 2)
         );
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Items {
+            a: Option<Vec<u32>>,
+        }
+
+        assert_ok!(
+            Items,
+            (root "123"),
+            Items { a: None }
+        );
+        assert_ok!(
+            Items,
+            (root "123" a: (child "456")),
+            Items { a: Some(vec![456]) }
+        );
+        assert_ok!(
+            Items,
+            (root "123" a: (child "456") a: (child "789")),
+            Items {
+                a: Some(vec![456, 789])
+            }
+        );
+    }
+
+    #[test]
+    fn test_struct_default() {
+        fn default_name() -> String {
+            "anonymous".into()
+        }
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root {
+            #[serde(default = "default_name")]
+            name: String,
+            #[serde(default)]
+            count: u32,
+            #[serde(default)]
+            pair: (u32, u32),
+            value: u32,
+        }
+
+        assert_ok!(
+            Root,
+            (root value: (child "1")),
+            Root {
+                name: "anonymous".into(),
+                count: 0,
+                pair: (0, 0),
+                value: 1,
+            }
+        );
+        assert_ok!(
+            Root,
+            (root name: (child "x") count: (child "2") pair: (child "3") pair: (child "4") value: (child "1")),
+            Root {
+                name: "x".into(),
+                count: 2,
+                pair: (3, 4),
+                value: 1,
+            }
+        );
+        // Fields without default value are still required
+        assert_err!(
+            Root,
+            (root name: (child "x")),
+            DeserializeError::field_length("value", 1, 0)
+        );
+        // Default values are used only if the field is empty
+        assert_err!(
+            Root,
+            (root count: (child "1") count: (child "2") value: (child "1")),
+            DeserializeError::field_length("count", 1, 2)
+        );
     }
 
     #[test]
@@ -570,7 +1965,7 @@ mod test {
         assert_err!(
             (i32,),
             (root (child "xxx")),
-            DeserializeError::ParseIntError("xxx".parse::<i32>().unwrap_err())
+            DeserializeError::parse_int("xxx".parse::<i32>().unwrap_err(), "xxx", "child")
         );
 
         // arity = 2
@@ -588,207 +1983,3527 @@ mod test {
         assert_err!(
             (i32, u8),
             (root (child "123") (child "yyy")),
-            DeserializeError::ParseIntError("yyy".parse::<u8>().unwrap_err())
+            DeserializeError::parse_int("yyy".parse::<u8>().unwrap_err(), "yyy", "child")
         );
     }
 
     #[test]
-    fn test_vec() {
-        assert_ok!(Vec<i32>, (root), vec![]);
-        assert_ok!(Vec<i32>, (root (child "123") (child "456")), vec![123, 456]);
-    }
-
-    #[test]
+    fn test_array() {
+        assert_ok!(
+            [u32; 3],
+            (root (child "1") (child "2") (child "3")),
+            [1, 2, 3]
+        );
+        assert_ok!([u32; 0], (root), []);
+        assert_err!(
+            [u32; 3],
+            (root (child "1") (child "2")),
+            DeserializeError::child_length(3, 2)
+        );
+        assert_err!(
+            [u32; 3],
+            (root (child "1") (child "2") (child "3") (child "4")),
+            DeserializeError::child_length(3, 4)
+        );
+        assert_err!([u32; 0], (root (child "1")), DeserializeError::child_length(0, 1));
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Newtype([u32; 2]);
+        assert_ok!(
+            Newtype,
+            (root (child "1") (child "2")),
+            Newtype([1, 2])
+        );
+        assert_err!(
+            Newtype,
+            (root (child "1")),
+            DeserializeError::child_length(2, 1)
+        );
+        assert_err!(
+            Newtype,
+            (root (child "1") (child "2") (child "3")),
+            DeserializeError::child_length(2, 3)
+        );
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root {
+            a: [u32; 2],
+        }
+        assert_ok!(
+            Root,
+            (root a: (child "1") a: (child "2")),
+            Root { a: [1, 2] }
+        );
+        assert_err!(
+            Root,
+            (root a: (child "1")),
+            DeserializeError::field_length("a", 2, 1)
+        );
+    }
+
+    #[test]
+    fn test_lenient_tuple() {
+        let options = || FromNodeOptions::new().lenient_tuple(true);
+        let node = make_node!(root (child "1") (sep ",") (child "2"));
+        assert_eq!(
+            deserialize_with_options::<(i32, String)>(&node, options()).unwrap(),
+            (1, ",".into())
+        );
+        assert_eq!(
+            deserialize_with_options::<(i32, Vec<String>)>(&node, options()).unwrap(),
+            (1, vec![",".into(), "2".into()])
+        );
+        assert_eq!(
+            deserialize_with_options::<(i32, String, u8, u8)>(&node, options()).unwrap_err(),
+            DeserializeError::child_length(4, 3)
+        );
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root((i32,));
+        assert_eq!(
+            deserialize_with_options::<Root>(&node, options()).unwrap(),
+            Root((1,))
+        );
+
+        // Strict by default
+        assert_err!(
+            (i32, String),
+            (root (child "1") (sep ",") (child "2")),
+            DeserializeError::child_length(2, 3)
+        );
+        assert_err!(
+            Root,
+            (root (child "1") (sep ",") (child "2")),
+            DeserializeError::child_length(1, 3)
+        );
+    }
+
+    #[test]
+    fn test_string_like() {
+        // Third-party string types read the text via `deserialize_str`
+        struct Ident(String);
+        impl<'de> Deserialize<'de> for Ident {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct Visitor;
+                impl<'de> serde::de::Visitor<'de> for Visitor {
+                    type Value = Ident;
+                    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        f.write_str("a string")
+                    }
+                    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Ident, E> {
+                        Ok(Ident(v.to_owned()))
+                    }
+                }
+                deserializer.deserialize_str(Visitor)
+            }
+        }
+        assert_eq!(
+            deserialize::<Ident>(&make_node!(identifier "foo"))
+                .unwrap()
+                .0,
+            "foo"
+        );
+        assert_ok!(Box<str>, (identifier "foo"), "foo".into());
+    }
+
+    #[test]
+    #[cfg(feature = "compact_str")]
+    fn test_compact_str() {
+        use compact_str::CompactString;
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "function")]
+        struct Function {
+            name: CompactString,
+            params: Vec<CompactString>,
+        }
+
+        assert_ok!(CompactString, (identifier "foo"), CompactString::from("foo"));
+        assert_ok!(
+            Function,
+            (function name: (identifier "f") params: (identifier "a") params: (identifier "b")),
+            Function {
+                name: "f".into(),
+                params: vec!["a".into(), "b".into()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_cow_str() {
+        use std::borrow::Cow;
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root<'a> {
+            #[serde(borrow)]
+            a: Cow<'a, str>,
+            b: String,
+        }
+
+        let node = make_node!(root a: (identifier "foo") b: (identifier "bar"));
+        let root = deserialize::<Root>(&node).unwrap();
+        assert_eq!(
+            root,
+            Root {
+                a: Cow::Borrowed("foo"),
+                b: "bar".into(),
+            }
+        );
+        assert!(
+            matches!(root.a, Cow::Borrowed(a) if a.as_ptr() == node.named_children[0].1.src.as_ptr())
+        );
+
+        // `deserialize_string` offers the borrowed text too
+        struct Borrowed<'a>(&'a str);
+        impl<'de> Deserialize<'de> for Borrowed<'de> {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct Visitor;
+                impl<'de> serde::de::Visitor<'de> for Visitor {
+                    type Value = Borrowed<'de>;
+                    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        f.write_str("a borrowed string")
+                    }
+                    fn visit_borrowed_str<E: serde::de::Error>(
+                        self,
+                        v: &'de str,
+                    ) -> Result<Self::Value, E> {
+                        Ok(Borrowed(v))
+                    }
+                }
+                deserializer.deserialize_string(Visitor)
+            }
+        }
+        let node = make_node!(identifier "foo");
+        let Borrowed(s) = deserialize::<Borrowed>(&node).unwrap();
+        assert_eq!(s.as_ptr(), node.src.as_ptr());
+    }
+
+    #[test]
+    fn test_nonzero() {
+        use std::num::{NonZeroI32, NonZeroU32, NonZeroU64, NonZeroU8};
+
+        assert_ok!(NonZeroU8, (root "123"), NonZeroU8::new(123).unwrap());
+        assert_ok!(NonZeroU32, (root "123"), NonZeroU32::new(123).unwrap());
+        assert_ok!(NonZeroU64, (root "123"), NonZeroU64::new(123).unwrap());
+        assert_ok!(NonZeroI32, (root "-123"), NonZeroI32::new(-123).unwrap());
+        assert_err!(
+            NonZeroU32,
+            (root "abc"),
+            DeserializeError::parse_int("abc".parse::<u32>().unwrap_err(), "abc", "root")
+        );
+        // zero is rejected by serde's NonZero impl
+        let err = deserialize::<NonZeroU32>(&make_node!(root "0")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid value: integer `0`, expected a nonzero u32"
+        );
+    }
+
+    #[test]
+    fn test_vec() {
+        assert_ok!(Vec<i32>, (root), vec![]);
+        assert_ok!(Vec<i32>, (root (child "123") (child "456")), vec![123, 456]);
+    }
+
+    #[test]
+    fn test_set() {
+        use std::collections::{BTreeSet, HashSet};
+
+        assert_ok!(
+            BTreeSet<i32>,
+            (root (child "2") (child "1") (child "2")),
+            BTreeSet::from([1, 2])
+        );
+        assert_ok!(
+            HashSet<&str>,
+            (root (child "a") (child "b") (child "a")),
+            HashSet::from(["a", "b"])
+        );
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root {
+            a: HashSet<String>,
+            b: BTreeSet<u32>,
+        }
+        assert_ok!(
+            Root,
+            (root a: (child "x") b: (child "2") a: (child "y") a: (child "x") b: (child "1")),
+            Root {
+                a: HashSet::from(["x".into(), "y".into()]),
+                b: BTreeSet::from([1, 2]),
+            }
+        );
+        assert_ok!(
+            Root,
+            (root),
+            Root {
+                a: HashSet::new(),
+                b: BTreeSet::new(),
+            }
+        );
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Newtype(BTreeSet<u32>);
+        assert_ok!(
+            Newtype,
+            (root (child "2") (child "1")),
+            Newtype(BTreeSet::from([1, 2]))
+        );
+    }
+
+    #[test]
+    fn test_int128() {
+        assert_ok!(
+            u128,
+            (root "340282366920938463463374607431768211455"),
+            u128::MAX
+        );
+        assert_ok!(
+            i128,
+            (root "-170141183460469231731687303715884105728"),
+            i128::MIN
+        );
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "child")]
+        struct Wrap(u128);
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root {
+            a: i128,
+            b: Wrap,
+        }
+        assert_ok!(
+            Root,
+            (root a: (child "-1") b: (child "18446744073709551616")),
+            Root {
+                a: -1,
+                b: Wrap(1 << 64)
+            }
+        );
+    }
+
+    #[test]
+    fn test_char() {
+        assert_ok!(char, (root "+"), '+');
+        assert_ok!(char, (root "あ"), 'あ');
+        assert_err!(char, (root ""), DeserializeError::InvalidChar("".into()));
+        assert_err!(char, (root "+="), DeserializeError::InvalidChar("+=".into()));
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root {
+            op: char,
+        }
+        assert_ok!(Root, (root op: (child "*")), Root { op: '*' });
+    }
+
+    #[test]
+    fn test_bytes() {
+        // `&[u8]` reads the node's text
+        assert_ok!(&[u8], (root "123" (child "1") (child "2")), "123".as_bytes());
+        // `Vec<u8>` reads named children as numbers
+        assert_ok!(Vec<u8>, (root "123" (child "1") (child "2")), vec![1, 2]);
+        assert_err!(
+            Vec<u8>,
+            (root "123" (child "256")),
+            DeserializeError::parse_int("256".parse::<u8>().unwrap_err(), "256", "child")
+        );
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root {
+            a: Vec<u8>,
+        }
+        assert_ok!(
+            Root,
+            (root a: (child "1") (child "2") a: (child "3")),
+            Root { a: vec![1, 3] }
+        );
+    }
+
+    #[test]
+    fn test_byte_buf() {
+        // Like `serde_bytes::ByteBuf`
+        #[derive(PartialEq, Eq, Debug)]
+        struct ByteBuf(Vec<u8>);
+        impl<'de> Deserialize<'de> for ByteBuf {
+            fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+                struct Visitor;
+                impl<'de> serde::de::Visitor<'de> for Visitor {
+                    type Value = ByteBuf;
+                    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        f.write_str("byte buf")
+                    }
+                    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+                        Ok(ByteBuf(v))
+                    }
+                }
+                d.deserialize_byte_buf(Visitor)
+            }
+        }
+
+        assert_ok!(ByteBuf, (root "abc"), ByteBuf(b"abc".to_vec()));
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "child")]
+        struct Wrap(ByteBuf);
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root {
+            a: ByteBuf,
+            b: Wrap,
+        }
+        assert_ok!(
+            Root,
+            (root a: (child "x") b: (child "yz")),
+            Root {
+                a: ByteBuf(b"x".to_vec()),
+                b: Wrap(ByteBuf(b"yz".to_vec()))
+            }
+        );
+    }
+
+    #[test]
+    fn test_reject_error_nodes() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root {
+            a: Vec<u32>,
+        }
+        let options = || FromNodeOptions::new().reject_error_nodes(true);
+
+        // Error nodes outside of the deserialized path are ignored
+        let node = make_node!(root a: (number "1") (ERROR "xx"));
+        assert_eq!(
+            deserialize_with_options::<Root>(&node, options()).unwrap(),
+            Root { a: vec![1] }
+        );
+
+        let node = make_node!(root a: (number "1") a: (ERROR "xx"));
+        assert_eq!(
+            deserialize_with_options::<Root>(&node, options()).unwrap_err(),
+            DeserializeError::ErrorNode(0..2)
+        );
+        assert_eq!(
+            deserialize::<Root>(&node).unwrap_err(),
+            DeserializeError::parse_int("xx".parse::<u32>().unwrap_err(), "xx", "ERROR").at(0..2)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_all_children() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        enum Token {
+            #[serde(rename = "[")]
+            Open,
+            #[serde(rename = "]")]
+            Close,
+            #[serde(rename = ",")]
+            Comma,
+            #[serde(rename = "number")]
+            Number(u32),
+        }
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "array")]
+        struct Array(AllChildren<Token>);
+
+        let src = "[1, 2]";
+        let tree = parser.parse(src, None).unwrap();
+        let array = tree.root_node().named_child(0).unwrap();
+        assert_eq!(
+            from_node::<Array>(array, src).unwrap(),
+            Array(AllChildren(vec![
+                Token::Open,
+                Token::Number(1),
+                Token::Comma,
+                Token::Number(2),
+                Token::Close,
+            ]))
+        );
+        assert_eq!(
+            from_node::<AllChildren<String>>(array, src).unwrap(),
+            AllChildren(
+                vec!["[", "1", ",", "2", "]"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect()
+            )
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_tree_value() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        let leaf = |kind: &str, text: &str| TreeValue {
+            kind: kind.into(),
+            text: text.into(),
+            children: vec![],
+        };
+
+        let src = r#"{"a": [1]}"#;
+        let tree = parser.parse(src, None).unwrap();
+        assert_eq!(
+            from_tree::<TreeValue>(&tree, src).unwrap(),
+            TreeValue {
+                kind: "document".into(),
+                text: src.into(),
+                children: vec![(
+                    None,
+                    TreeValue {
+                        kind: "object".into(),
+                        text: src.into(),
+                        children: vec![(
+                            None,
+                            TreeValue {
+                                kind: "pair".into(),
+                                text: r#""a": [1]"#.into(),
+                                children: vec![
+                                    (
+                                        Some("key".into()),
+                                        TreeValue {
+                                            kind: "string".into(),
+                                            text: r#""a""#.into(),
+                                            children: vec![(None, leaf("string_content", "a"))],
+                                        }
+                                    ),
+                                    (
+                                        Some("value".into()),
+                                        TreeValue {
+                                            kind: "array".into(),
+                                            text: "[1]".into(),
+                                            children: vec![(None, leaf("number", "1"))],
+                                        }
+                                    ),
+                                ],
+                            }
+                        )],
+                    }
+                )],
+            }
+        );
+
+        // Can be mixed with typed values
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "pair")]
+        struct Pair {
+            key: TreeValue,
+            value: TreeValue,
+        }
+        let pair = make_node!(pair key: (string "a") value: (number "1"));
+        assert_eq!(
+            deserialize::<Pair>(&pair).unwrap(),
+            Pair {
+                key: leaf("string", "a"),
+                value: leaf("number", "1"),
+            }
+        );
+        assert_eq!(describe::<TreeValue>(), "any node");
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_skip_error_nodes() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "document")]
+        struct Document(Array);
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "array")]
+        struct Array(u32, u32);
+
+        let options = || FromNodeOptions::new().skip_error_nodes(true);
+
+        let src = "[1, 2 3]";
+        let tree = parser.parse(src, None).unwrap();
+        assert_eq!(
+            from_tree_with_options::<Document>(&tree, src, options()).unwrap(),
+            Document(Array(1, 2))
+        );
+        assert!(matches!(
+            from_tree::<Document>(&tree, src).unwrap_err(),
+            DeserializeError::TreeSitterError(_)
+        ));
+
+        let src = "[1, 2";
+        let tree = parser.parse(src, None).unwrap();
+        assert_eq!(
+            from_tree_with_options::<Document>(&tree, src, options()).unwrap(),
+            Document(Array(1, 2))
+        );
+    }
+
+    #[test]
+    fn test_size_hint() {
+        /// Size hints given to `visit_seq`.
+        #[derive(Debug, PartialEq, Eq)]
+        struct SizeHint(Option<usize>);
+        impl<'de> serde::Deserialize<'de> for SizeHint {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct Visitor;
+                impl<'de> serde::de::Visitor<'de> for Visitor {
+                    type Value = SizeHint;
+                    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        f.write_str("a sequence")
+                    }
+                    fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                        self,
+                        mut seq: A,
+                    ) -> Result<Self::Value, A::Error> {
+                        let hint = seq.size_hint();
+                        while seq.next_element::<serde::de::IgnoredAny>()?.is_some() {}
+                        Ok(SizeHint(hint))
+                    }
+                }
+                deserializer.deserialize_seq(Visitor)
+            }
+        }
+
+        assert_ok!(
+            SizeHint,
+            (root (child "1") (child "2") (child "3")),
+            SizeHint(Some(3))
+        );
+        assert_ok!(SizeHint, (root), SizeHint(Some(0)));
+    }
+
+    #[test]
+    fn test_element_limit() {
+        let node = make_node!(root (child "1") (child "2") (child "3") (child "4"));
+        let options = |limit| FromNodeOptions::new().element_limit(limit);
+
+        assert_eq!(
+            deserialize_with_options::<Vec<u32>>(&node, options(4)).unwrap(),
+            vec![1, 2, 3, 4]
+        );
+        assert_eq!(
+            deserialize_with_options::<Vec<u32>>(&node, options(3)).unwrap_err(),
+            DeserializeError::ElementLimitExceeded { limit: 3 }
+        );
+
+        // The count is shared by nested sequences
+        let node = make_node!(root (child (x "1") (x "2")) (child (x "3") (x "4")));
+        assert_eq!(
+            deserialize_with_options::<Vec<Vec<u32>>>(&node, options(6)).unwrap(),
+            vec![vec![1, 2], vec![3, 4]]
+        );
+        assert_eq!(
+            deserialize_with_options::<Vec<Vec<u32>>>(&node, options(5)).unwrap_err(),
+            DeserializeError::ElementLimitExceeded { limit: 5 }
+        );
+
+        // Wrappers don't count the node itself
+        let node = make_node!(root (child "1") (child "2") (child "3"));
+        assert_eq!(
+            deserialize_with_options::<Vec<Located<u32>>>(&node, options(3))
+                .unwrap()
+                .into_iter()
+                .map(|v| v.value)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            deserialize_with_options::<Vec<Positioned<u32>>>(&node, options(3))
+                .unwrap()
+                .into_iter()
+                .map(|v| v.value)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn test_dump_node() {
+        let node = make_node!(root key: (string "\"a\"") value: (array (number "1")) (comment "// a long long comment"));
+        assert_eq!(
+            dump_node(&DummyRef::root(&node)),
+            [
+                r#"- root """#,
+                r#"  - key: string "\"a\"""#,
+                r#"  - value: array """#,
+                r#"    - number "1""#,
+                r#"  - comment "// a long long comme...""#,
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_deserialize_with_text_and_children() {
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "template")]
+        struct RawTemplate {
+            #[serde(rename = "$text")]
+            text: String,
+            #[serde(rename = "$rest")]
+            holes: Vec<String>,
+        }
+
+        fn template<'de, D: serde::Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<(String, usize), D::Error> {
+            let raw = RawTemplate::deserialize(deserializer)?;
+            Ok((raw.text, raw.holes.len()))
+        }
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root {
+            #[serde(deserialize_with = "template")]
+            body: (String, usize),
+        }
+
+        assert_ok!(
+            Root,
+            (root body: (template "a${x}b${y}" (hole "x") (hole "y"))),
+            Root {
+                body: ("a${x}b${y}".into(), 2)
+            }
+        );
+    }
+
+    #[test]
+    fn test_boxed_slice() {
+        use std::sync::Arc;
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root {
+            a: Box<[u32]>,
+            b: Rc<[u32]>,
+            c: Arc<[u32]>,
+        }
+
+        assert_ok!(
+            Root,
+            (root a: (x "1") a: (x "2") b: (x "3") c: (x "4") c: (x "5")),
+            Root {
+                a: vec![1, 2].into(),
+                b: vec![3].into(),
+                c: vec![4, 5].into(),
+            }
+        );
+        assert_ok!(
+            Root,
+            (root),
+            Root {
+                a: vec![].into(),
+                b: vec![].into(),
+                c: vec![].into(),
+            }
+        );
+        assert_ok!(Box<[u32]>, (root (x "1") (x "2")), vec![1, 2].into());
+        assert_ok!(Rc<[u32]>, (root (x "1")), vec![1].into());
+    }
+
+    #[test]
+    fn test_rename_all_kinds() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename_all = "kebab-case")]
+        enum Kebab {
+            StringContent(String),
+            NullLiteral,
+            KeyValue { key_name: String },
+        }
+        let node = |kind, src, children| DummyNode::new(kind, src, children);
+        let root = node(
+            "root",
+            "",
+            vec![
+                (None, node("string-content", "a", vec![])),
+                (None, node("null-literal", "", vec![])),
+                (
+                    None,
+                    node(
+                        "key-value",
+                        "",
+                        vec![(Some("key_name"), node("x", "k", vec![]))],
+                    ),
+                ),
+            ],
+        );
+        assert_eq!(
+            deserialize::<Vec<Kebab>>(&root).unwrap(),
+            vec![
+                Kebab::StringContent("a".into()),
+                Kebab::NullLiteral,
+                Kebab::KeyValue {
+                    key_name: "k".into()
+                }
+            ]
+        );
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+        enum Screaming {
+            StringContent(String),
+            NullLiteral,
+        }
+        assert_ok!(
+            Vec<Screaming>,
+            (root (STRING_CONTENT "a") (NULL_LITERAL)),
+            vec![
+                Screaming::StringContent("a".into()),
+                Screaming::NullLiteral
+            ]
+        );
+
+        // Structs are matched by their name, that `rename_all` doesn't change
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "key-value", rename_all = "kebab-case")]
+        struct KeyValue {
+            key_name: String,
+        }
+        let key_value = node(
+            "key-value",
+            "",
+            vec![(Some("key-name"), node("x", "k", vec![]))],
+        );
+        assert_eq!(
+            deserialize::<KeyValue>(&key_value).unwrap(),
+            KeyValue {
+                key_name: "k".into()
+            }
+        );
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "KEY_VALUE", rename_all = "SCREAMING_SNAKE_CASE")]
+        struct ScreamingKeyValue {
+            key_name: String,
+        }
+        assert_ok!(
+            ScreamingKeyValue,
+            (KEY_VALUE KEY_NAME: (x "k")),
+            ScreamingKeyValue {
+                key_name: "k".into()
+            }
+        );
+    }
+
+    #[test]
+    fn test_ignored_any() {
+        use serde::de::IgnoredAny;
+
+        let node = make_node!(root (a (x (ERROR "e"))) (b "1") (c (ERROR "e")) (d "2"));
+        // Subtrees of ignored children are not entered
+        let options = || FromNodeOptions::new().reject_error_nodes(true).max_depth(2);
+        assert!(deserialize_with_options::<Vec<TreeValue>>(&node, options()).is_err());
+
+        assert_eq!(
+            deserialize_with_options::<Vec<IgnoredAny>>(&node, options())
+                .unwrap()
+                .len(),
+            4
+        );
+        assert_eq!(
+            deserialize_with_options::<(IgnoredAny, u32, IgnoredAny, u32)>(&node, options())
+                .unwrap(),
+            (IgnoredAny, 1, IgnoredAny, 2)
+        );
+        assert_eq!(
+            deserialize_with_options::<(IgnoredAny, u32, Vec<IgnoredAny>)>(&node, options())
+                .unwrap()
+                .2
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_max_depth() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "n")]
+        struct N(Vec<N>);
+
+        let mut node = DummyNode::new("n", "", vec![]);
+        for _ in 1..5 {
+            node = DummyNode::new("n", "", vec![(None, node)]);
+        }
+        let options = |limit| FromNodeOptions::new().max_depth(limit);
+
+        assert_eq!(
+            deserialize_with_options::<N>(&node, options(5)).unwrap(),
+            N(vec![N(vec![N(vec![N(vec![N(vec![])])])])])
+        );
+        assert_eq!(
+            deserialize_with_options::<N>(&node, options(4)).unwrap_err(),
+            DeserializeError::DepthLimitExceeded { limit: 4 }.at(0..0)
+        );
+    }
+
+    #[test]
+    fn test_cow_bytes() {
+        use std::borrow::Cow;
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root<'a> {
+            #[serde(borrow)]
+            a: Cow<'a, [u8]>,
+            b: Cow<'a, [u8]>,
+        }
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "data")]
+        struct Data<'a>(#[serde(borrow)] Cow<'a, [u8]>);
+
+        // `#[serde(borrow)]` borrows the source, and `Cow<[u8]>` without it follows `Vec<u8>`
+        let node = make_node!(root a: (data "abc") b: (number "1") b: (number "2"));
+        let root = deserialize::<Root>(&node).unwrap();
+        assert_eq!(
+            root,
+            Root {
+                a: Cow::Borrowed(b"abc"),
+                b: Cow::Owned(vec![1, 2]),
+            }
+        );
+        let Cow::Borrowed(a) = root.a else {
+            panic!("not borrowed: {:?}", root.a);
+        };
+        assert_eq!(a.as_ptr(), node.named_children[0].1.src.as_ptr());
+        assert!(matches!(root.b, Cow::Owned(_)));
+
+        let node = make_node!(data "abc");
+        let Data(data) = deserialize::<Data>(&node).unwrap();
+        assert!(matches!(data, Cow::Borrowed(b) if b.as_ptr() == node.src.as_ptr()));
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_struct_field_bytes() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "pair")]
+        struct Pair<'a> {
+            key: &'a [u8],
+            value: Option<&'a [u8]>,
+        }
+
+        let src = b"{\"a\": \"b\xff\"}".to_vec();
+        let tree = parser.parse(&src, None).unwrap();
+        let pair = tree
+            .root_node()
+            .named_child(0)
+            .unwrap()
+            .named_child(0)
+            .unwrap();
+        let pair = from_node_bytes::<Pair>(pair, &src).unwrap();
+        assert_eq!(
+            pair,
+            Pair {
+                key: br#""a""#,
+                value: Some(b"\"b\xff\""),
+            }
+        );
+        // Borrowed from the source without copying
+        let range = src.as_ptr_range();
+        assert!(range.contains(&pair.key.as_ptr()));
+        assert!(range.contains(&pair.value.unwrap().as_ptr()));
+    }
+
+    #[test]
     fn test_option() {
         assert_ok!(Option<i32>, (root), None);
         assert_ok!(Option<i32>, (root (child "123")), Some(123));
         assert_err!(Option<i32>, (root (child "123") (child "456")), DeserializeError::child_length(1, 2));
+
+        // Matches the node's children, not the node itself.
+        assert_ok!(Option<i32>, (root "1"), None);
+
+        // In a struct field, matches the node in the field.
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root {
+            a: Option<i32>,
+        }
+        assert_ok!(Root, (root a: (int "1")), Root { a: Some(1) });
+        assert_ok!(Root, (root (int "1")), Root { a: None });
+    }
+
+    #[test]
+    fn test_enum() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename_all = "snake_case")]
+        enum Value {
+            // Unit variant
+            Null,
+            // Newtype variant
+            Int(i64),
+            // Tuple variant
+            Tuple(String, i32),
+            // Struct variant
+            Struct {
+                a: u32,
+                b: Vec<String>,
+                c: Option<String>,
+            },
+        }
+
+        // unit
+        assert_eq!(
+            deserialize::<Value>(&make_node!(null "foo")).unwrap(),
+            Value::Null,
+        );
+
+        // newtype(ok)
+        assert_eq!(
+            deserialize::<Value>(&make_node!(int "999")).unwrap(),
+            Value::Int(999),
+        );
+
+        // TODO: newtype(error)
+
+        // tuple(ok)
+        assert_eq!(
+            deserialize::<Value>(&make_node!(tuple "999" (c1 "foo") (c2 "333"))).unwrap(),
+            Value::Tuple("foo".into(), 333),
+        );
+
+        // tuple(error: child count)
+        assert_eq!(
+            deserialize::<Value>(&make_node!(tuple "999" (c1 "foo"))).unwrap_err(),
+            DeserializeError::variant_child_length("Value", "tuple", "tuple", 2, 1),
+        );
+        assert_eq!(
+            deserialize::<Value>(&make_node!(tuple "999" (c1 "foo") (c2 "1") (c3 "2")))
+                .unwrap_err()
+                .to_string(),
+            "Node kind `tuple` matched variant `tuple` of `Value`, but child count not match: expected=2, actual=3",
+        );
+        assert_eq!(
+            deserialize_with_options::<Value>(
+                &make_node!(TUPLE "999" (c1 "foo")),
+                FromNodeOptions::new().case_insensitive_kinds(true)
+            )
+            .unwrap_err()
+            .to_string(),
+            "Node kind `TUPLE` matched variant `tuple` of `Value`, but child count not match: expected=2, actual=1",
+        );
+
+        // tuple(error: type error)
+        assert_eq!(
+            deserialize::<Value>(&make_node!(tuple "999" (c1 "foo") (c2 "not_a_number")))
+                .unwrap_err(),
+            DeserializeError::parse_int(
+                "not_a_number".parse::<i32>().unwrap_err(),
+                "not_a_number",
+                "c2"
+            )
+            .at(0..12)
+        );
+
+        // struct(ok: b = [...], c = None)
+        assert_eq!(
+            deserialize::<Value>(&make_node!(struct ""
+                a: (foo "123")
+                b: (bar "a")
+                b: (bar "b")
+                (baz)
+                b: (bar "c")
+            ))
+            .unwrap(),
+            Value::Struct {
+                a: 123,
+                b: ["a", "b", "c"].into_iter().map(|x| x.to_owned()).collect(),
+                c: None,
+            }
+        );
+
+        // struct(ok: b = [], c = "foo")
+        assert_eq!(
+            deserialize::<Value>(&make_node!(struct ""
+                a: (foo "123")
+                (baz)
+                c: (foo "foo")
+            ))
+            .unwrap(),
+            Value::Struct {
+                a: 123,
+                b: vec![],
+                c: Some("foo".into()),
+            }
+        );
+        // struct(error: missing a)
+        assert_eq!(
+            deserialize::<Value>(&make_node!(struct ""
+                b: (foo "123")
+                (baz)
+            ))
+            .unwrap_err(),
+            DeserializeError::field_length("a", 1, 0)
+        );
+        // struct(error: option field length > 1)
+        assert_eq!(
+            deserialize::<Value>(&make_node!(struct ""
+                a: (foo "123")
+                (baz)
+                c: (foo "foo")
+                c: (foo "foo")
+            ))
+            .unwrap_err(),
+            DeserializeError::field_length("c", 1, 2)
+        );
+        // error: unknown variant
+        let err = deserialize::<Value>(&make_node!(unknown ""
+            a: (foo "123")
+            (baz)
+            c: (foo "foo")
+            c: (foo "foo")
+        ))
+        .unwrap_err();
+        assert_eq!(
+            err.without_range(),
+            &DeserializeError::UnknownVariant {
+                kind: "unknown".into(),
+                expected: vec!["null", "int", "tuple", "struct"]
+            }
+        );
+        assert_eq!(
+            err.without_range().to_string(),
+            "Unknown variant `unknown`, expected one of: [`null`, `int`, `tuple`, `struct`]"
+        );
+    }
+
+    #[test]
+    fn test_variant_child_kinds() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        enum Literal {
+            Number(String),
+            Text(String),
+            Nil,
+        }
+        let options = || {
+            FromNodeOptions::new()
+                .variant_child_kinds("Literal", "Number", &["integer", "float"])
+                .variant_child_kinds("Literal", "Text", &["string", "char"])
+        };
+
+        assert_eq!(
+            deserialize_with_options::<Literal>(&make_node!(literal (float "1.5")), options())
+                .unwrap(),
+            Literal::Number("1.5".into())
+        );
+        // Matches the second kind of the set
+        assert_eq!(
+            deserialize_with_options::<Literal>(
+                &make_node!(literal (comment "c") (char "x")),
+                options()
+            )
+            .unwrap(),
+            Literal::Text("x".into())
+        );
+        // Falls back to the node's kind
+        assert_eq!(
+            deserialize_with_options::<Literal>(&make_node!(Nil), options()).unwrap(),
+            Literal::Nil
+        );
+    }
+
+    #[test]
+    fn test_child_kinds() {
+        assert_ok!(
+            ChildKinds,
+            (root "xxx" (a "1") (b "2") (a "3")),
+            ChildKinds(vec!["a".into(), "b".into(), "a".into()])
+        );
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root {
+            #[serde(deserialize_with = "child_kinds")]
+            a: Vec<String>,
+        }
+        assert_ok!(
+            Root,
+            (root a: (child (int "1") (str "x") (int "2"))),
+            Root { a: vec!["int".into(), "str".into(), "int".into()] }
+        );
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Newtype(ChildKinds);
+        assert_ok!(
+            Newtype,
+            (root (int "1") (str "x")),
+            Newtype(ChildKinds(vec!["int".into(), "str".into()]))
+        );
+    }
+
+    #[test]
+    fn test_node_kind() {
+        assert_ok!(NodeKind, (plus "+"), NodeKind("plus".into()));
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "binary")]
+        struct Binary {
+            left: u32,
+            operator: NodeKind,
+            right: u32,
+        }
+        assert_ok!(
+            Binary,
+            (binary left: (int "1") operator: (minus "-") right: (int "2")),
+            Binary {
+                left: 1,
+                operator: NodeKind("minus".into()),
+                right: 2
+            }
+        );
+        assert_ok!(
+            Vec<NodeKind>,
+            (root (int "1") (str "x")),
+            vec![NodeKind("int".into()), NodeKind("str".into())]
+        );
+    }
+
+    #[test]
+    fn test_maybe_kind() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "ident")]
+        struct Ident(String);
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename_all = "snake_case")]
+        enum Literal {
+            Int(i32),
+            Str(String),
+        }
+
+        assert_ok!(MaybeKind<Ident>, (ident "x"), MaybeKind(Some(Ident("x".into()))));
+        assert_ok!(MaybeKind<Ident>, (int "1"), MaybeKind(None));
+        // Children are not counted
+        assert_ok!(
+            MaybeKind<Ident>,
+            (ident "x" (a "1") (b "2")),
+            MaybeKind(Some(Ident("x".into())))
+        );
+
+        assert_ok!(MaybeKind<Literal>, (int "1"), MaybeKind(Some(Literal::Int(1))));
+        assert_ok!(MaybeKind<Literal>, (ident "x"), MaybeKind(None));
+
+        // Errors after the kind matched are reported
+        assert_err!(
+            MaybeKind<Literal>,
+            (int "x"),
+            DeserializeError::parse_int("x".parse::<i32>().unwrap_err(), "x", "int")
+        );
+
+        // In fields
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root {
+            a: MaybeKind<Ident>,
+        }
+        assert_ok!(
+            Root,
+            (root a: (ident "x")),
+            Root { a: MaybeKind(Some(Ident("x".into()))) }
+        );
+        assert_ok!(Root, (root a: (int "1")), Root { a: MaybeKind(None) });
+    }
+
+    #[test]
+    fn test_presence() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root {
+            a: Presence<u32>,
+            b: Presence<Vec<u32>>,
+            c: Presence<Option<u32>>,
+        }
+
+        assert_ok!(
+            Root,
+            (root a: (number "0") b: (number "1") b: (number "2") c: (number "3")),
+            Root {
+                a: Presence { value: 0, present: true },
+                b: Presence { value: vec![1, 2], present: true },
+                c: Presence { value: Some(3), present: true },
+            }
+        );
+        assert_ok!(
+            Root,
+            (root),
+            Root {
+                a: Presence {
+                    value: 0,
+                    present: false
+                },
+                b: Presence {
+                    value: vec![],
+                    present: false
+                },
+                c: Presence {
+                    value: None,
+                    present: false
+                },
+            }
+        );
+        assert_err!(
+            Root,
+            (root a: (number "1") a: (number "2")),
+            DeserializeError::field_length("a", 1, 2)
+        );
+        assert_ok!(
+            Presence<u32>,
+            (number "1"),
+            Presence { value: 1, present: true }
+        );
+    }
+
+    #[test]
+    fn test_by_child() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename_all = "snake_case")]
+        enum Visibility {
+            Pub,
+            Priv,
+        }
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "function")]
+        struct Function {
+            visibility: ByChild<Visibility>,
+        }
+
+        assert_ok!(
+            ByChild<Visibility>,
+            (visibility (priv)),
+            ByChild(Visibility::Priv)
+        );
+        assert_ok!(
+            Function,
+            (function visibility: (visibility (pub))),
+            Function { visibility: ByChild(Visibility::Pub) }
+        );
+        assert_err!(
+            ByChild<Visibility>,
+            (visibility),
+            DeserializeError::child_length(1, 0)
+        );
+        assert_err!(
+            ByChild<Visibility>,
+            (visibility (pub) (priv)),
+            DeserializeError::child_length(1, 2)
+        );
+    }
+
+    #[test]
+    fn test_first_last() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root {
+            first: First<u32>,
+            last: Last<u32>,
+        }
+        assert_ok!(
+            Root,
+            (root
+                first: (int "1") first: (int "2") first: (int "3")
+                last: (int "4") last: (int "5") last: (int "6")),
+            Root {
+                first: First(1),
+                last: Last(6)
+            }
+        );
+        assert_ok!(
+            Root,
+            (root first: (int "1") last: (int "2")),
+            Root {
+                first: First(1),
+                last: Last(2)
+            }
+        );
+        assert_err!(
+            Root,
+            (root last: (int "2")),
+            DeserializeError::field_length("first", 1, 0)
+        );
+
+        assert_ok!(First<u32>, (int "1"), First(1));
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Newtype(Last<u32>);
+        assert_ok!(Newtype, (root "1"), Newtype(Last(1)));
+    }
+
+    #[test]
+    fn test_joined() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "root")]
+        struct Root {
+            part: Joined,
+        }
+        assert_ok!(
+            Root,
+            (root part: (str "foo") (comment "x") part: (str "bar") part: (str "baz")),
+            Root {
+                part: Joined("foobarbaz".into())
+            }
+        );
+        assert_ok!(
+            Root,
+            (root),
+            Root {
+                part: Joined("".into())
+            }
+        );
+        assert_ok!(Joined, (str "foo" (a "f") (b "oo")), Joined("foo".into()));
+
+        // The text is not normalized, as for `String`
+        let options = || FromNodeOptions::new().text_normalization(TextNormalization::Lowercase);
+        assert_eq!(
+            deserialize_with_options::<Root>(
+                &make_node!(root part: (str "Foo") part: (str "BAR")),
+                options()
+            )
+            .unwrap(),
+            Root {
+                part: Joined("FooBAR".into())
+            }
+        );
+        assert_eq!(
+            deserialize_with_options::<Joined>(&make_node!(str "Foo"), options()).unwrap(),
+            Joined("Foo".into())
+        );
+    }
+
+    #[test]
+    fn test_flag() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "function")]
+        struct Function {
+            is_async: Flag,
+            name: String,
+        }
+
+        assert_ok!(
+            Function,
+            (function is_async: (async_keyword "async") name: (ident "f")),
+            Function { is_async: Flag(true), name: "f".into() }
+        );
+        assert_ok!(
+            Function,
+            (function name: (ident "f")),
+            Function { is_async: Flag(false), name: "f".into() }
+        );
+        assert_err!(
+            Function,
+            (function is_async: (a "async") is_async: (a "async") name: (ident "f")),
+            DeserializeError::field_length("is_async", 1, 2)
+        );
+        assert_ok!(Flag, (root "xxx"), Flag(true));
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_span() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "pair")]
+        struct Pair {
+            #[serde(rename = "$span")]
+            span: Span,
+            key: Span,
+            value: Span,
+        }
+        impl SourceSpanned for Pair {
+            fn span(&self) -> Span {
+                self.span
+            }
+        }
+
+        let src = r#"{"a": 1, "bb" :[ 2 ]}"#;
+        let tree = parser.parse(src, None).unwrap();
+        let object = tree.root_node().named_child(0).unwrap();
+        let pairs: Vec<Pair> = from_node(object, src).unwrap();
+
+        assert_eq!(
+            pairs,
+            vec![
+                Pair {
+                    span: (1..7).into(),
+                    key: (1..4).into(),
+                    value: (6..7).into(),
+                },
+                Pair {
+                    span: (9..20).into(),
+                    key: (9..13).into(),
+                    value: (15..20).into(),
+                },
+            ]
+        );
+        assert_eq!(reassemble(src, &pairs[0]), r#""a": 1"#);
+        assert_eq!(reassemble(src, &pairs[1]), r#""bb" :[ 2 ]"#);
+        assert_eq!(reassemble(src, &pairs[1].value), "[ 2 ]");
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_span_vec() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        let src = r#"[1, "ab", [], null]"#;
+        let tree = parser.parse(src, None).unwrap();
+        let array = tree.root_node().named_child(0).unwrap();
+        let spans: Vec<Span> = from_node(array, src).unwrap();
+
+        let mut cursor = array.walk();
+        let expected = array
+            .named_children(&mut cursor)
+            .map(|c| Span::from(c.byte_range()))
+            .collect::<Vec<_>>();
+        assert_eq!(spans, expected);
+        assert_eq!(
+            spans,
+            vec![
+                (1..2).into(),
+                (4..8).into(),
+                (10..12).into(),
+                (14..18).into()
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_spanned() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "pair")]
+        struct Pair<'a> {
+            #[serde(borrow)]
+            key: Spanned<&'a str>,
+            value: Spanned<Vec<u32>>,
+        }
+
+        let src = r#"{"a": [1, 2]}"#;
+        let tree = parser.parse(src, None).unwrap();
+        let pair = tree
+            .root_node()
+            .named_child(0)
+            .unwrap()
+            .named_child(0)
+            .unwrap();
+        let pair: Pair = from_node(pair, src).unwrap();
+        assert_eq!(
+            pair,
+            Pair {
+                key: Spanned {
+                    value: r#""a""#,
+                    range: 1..4
+                },
+                value: Spanned {
+                    value: vec![1, 2],
+                    range: 6..12
+                },
+            }
+        );
+        assert_eq!(reassemble(src, &pair.value), "[1, 2]");
+    }
+
+    #[test]
+    fn test_positioned() {
+        let node = make_node!(root "abc").at((1, 2), (3, 4));
+        assert_eq!(
+            deserialize::<Positioned<String>>(&node).unwrap(),
+            Positioned {
+                value: "abc".into(),
+                start: Point::new(1, 2),
+                end: Point::new(3, 4),
+            }
+        );
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "decl")]
+        struct Decl {
+            name: Positioned<String>,
+        }
+        let node = DummyNode::new(
+            "decl",
+            "let\n  x",
+            vec![(Some("name"), make_node!(identifier "x").at((1, 2), (1, 3)))],
+        );
+        assert_eq!(
+            deserialize::<Decl>(&node).unwrap(),
+            Decl {
+                name: Positioned {
+                    value: "x".into(),
+                    start: Point::new(1, 2),
+                    end: Point::new(1, 3),
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn test_located() {
+        assert_ok!(
+            Located<String>,
+            (root "abc"),
+            Located { value: "abc".into(), span: Span { start: 0, end: 3 } }
+        );
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "decl")]
+        struct Decl {
+            name: Located<String>,
+        }
+        assert_ok!(
+            Decl,
+            (decl "let x" name: (identifier "x")),
+            Decl { name: Located { value: "x".into(), span: Span { start: 0, end: 1 } } }
+        );
+        assert_err!(
+            Decl,
+            (decl "let"),
+            DeserializeError::field_length("name", 1, 0)
+        );
+        assert_err!(
+            Decl,
+            (decl "let x y" name: (identifier "x") name: (identifier "y")),
+            DeserializeError::field_length("name", 1, 2)
+        );
+    }
+
+    #[test]
+    fn test_kind_aliases() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "function_declaration")]
+        struct Function {
+            name: String,
+        }
+
+        let options = || {
+            FromNodeOptions::new().kind_aliases("function_declaration", &["function_definition"])
+        };
+
+        assert_eq!(
+            deserialize_with_options::<Vec<Function>>(
+                &make_node!(root
+                    (function_declaration name: (identifier "f"))
+                    (function_definition name: (identifier "g"))),
+                options()
+            )
+            .unwrap(),
+            vec![Function { name: "f".into() }, Function { name: "g".into() }]
+        );
+        assert_err!(
+            Function,
+            (function_definition name: (identifier "g")),
+            DeserializeError::node_type("function_declaration", "function_definition")
+        );
+    }
+
+    #[test]
+    fn test_unwrap_single_child() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "binary")]
+        struct Binary {
+            left: u32,
+            right: u32,
+        }
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename_all = "snake_case")]
+        enum Expr {
+            Binary { left: u32, right: u32 },
+            Int(u32),
+        }
+
+        let node = make_node!(expression (binary left: (int "1") right: (int "2")));
+        assert_eq!(
+            deserialize::<Binary>(&node).unwrap_err(),
+            DeserializeError::node_type("binary", "expression")
+                .at(0..0)
+                .with_path(vec!["expression"])
+        );
+        let options = || FromNodeOptions::new().unwrap_single_child(1);
+        assert_eq!(
+            deserialize_with_options::<Binary>(&node, options()).unwrap(),
+            Binary { left: 1, right: 2 }
+        );
+        assert_eq!(
+            deserialize_with_options::<Expr>(&node, options()).unwrap(),
+            Expr::Binary { left: 1, right: 2 }
+        );
+
+        // Bounded by the depth
+        let node = make_node!(statement (expression (int "1")));
+        assert!(deserialize_with_options::<Expr>(&node, options()).is_err());
+        assert_eq!(
+            deserialize_with_options::<Expr>(&node, FromNodeOptions::new().unwrap_single_child(2))
+                .unwrap(),
+            Expr::Int(1)
+        );
+
+        // Only a single named child is unwrapped
+        let node = make_node!(expression (int "1") (int "2"));
+        assert!(deserialize_with_options::<Expr>(&node, options()).is_err());
+
+        // Errors in the unwrapped node have the path through the wrappers
+        #[derive(serde::Deserialize, Debug)]
+        #[serde(rename = "int")]
+        struct Int(#[allow(dead_code)] u32);
+        #[derive(serde::Deserialize, Debug)]
+        #[serde(rename = "binary")]
+        #[allow(dead_code)]
+        struct TypedBinary {
+            left: Int,
+            right: Int,
+        }
+        #[derive(serde::Deserialize, Debug)]
+        #[serde(rename = "statement")]
+        struct Statement(#[allow(dead_code)] TypedBinary);
+        let node = make_node!(statement (expression (binary left: (str "x") right: (int "2"))));
+        assert_eq!(
+            deserialize_with_options::<Statement>(&node, options())
+                .unwrap_err()
+                .path(),
+            &["statement", "expression", "binary", "str"][..]
+        );
+    }
+
+    #[test]
+    fn test_wrapper_enum() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename_all = "snake_case")]
+        enum Statement {
+            IfStatement { condition: u32 },
+            WhileStatement { condition: u32 },
+        }
+
+        let node = make_node!(statement (while_statement condition: (int "1")));
+        assert_eq!(
+            deserialize::<ByChild<Statement>>(&node).unwrap(),
+            ByChild(Statement::WhileStatement { condition: 1 })
+        );
+        assert_eq!(
+            deserialize_with_options::<Statement>(
+                &node,
+                FromNodeOptions::new().unwrap_single_child(1)
+            )
+            .unwrap(),
+            Statement::WhileStatement { condition: 1 }
+        );
+        let node = make_node!(statement (if_statement condition: (int "2")));
+        assert_eq!(
+            deserialize::<ByChild<Statement>>(&node).unwrap(),
+            ByChild(Statement::IfStatement { condition: 2 })
+        );
+
+        // Without either, the variant is selected by the wrapper's kind
+        assert_err!(
+            Statement,
+            (statement (if_statement condition: (int "2"))),
+            DeserializeError::UnknownVariant {
+                kind: "statement".into(),
+                expected: vec!["if_statement", "while_statement"]
+            }
+        );
+    }
+
+    #[test]
+    fn test_case_insensitive_kinds() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "string")]
+        struct String(std::string::String);
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename_all = "snake_case")]
+        enum Value {
+            Number(u32),
+            String(std::string::String),
+        }
+
+        let options = || FromNodeOptions::new().case_insensitive_kinds(true);
+
+        assert_eq!(
+            deserialize_with_options::<String>(&make_node!(STRING "a"), options()).unwrap(),
+            String("a".into())
+        );
+        assert_err!(
+            String,
+            (STRING "a"),
+            DeserializeError::node_type("string", "STRING")
+        );
+        assert_eq!(
+            deserialize_with_options::<Vec<Value>>(
+                &make_node!(root (STRING "a") (Number "1")),
+                options()
+            )
+            .unwrap(),
+            vec![Value::String("a".into()), Value::Number(1)]
+        );
+        assert_err!(
+            Value,
+            (STRING "a"),
+            DeserializeError::UnknownVariant {
+                kind: "STRING".into(),
+                expected: vec!["number", "string"]
+            }
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_kind_pattern() {
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename = "statement")]
+        struct Statement(String);
+
+        let options = || {
+            FromNodeOptions::new()
+                .kind_pattern("statement", regex::Regex::new(r"[a-z]+_statement").unwrap())
+        };
+
+        assert_eq!(
+            deserialize_with_options::<Statement>(&make_node!(if_statement "a"), options())
+                .unwrap(),
+            Statement("a".into())
+        );
+        assert_eq!(
+            deserialize_with_options::<Vec<Statement>>(
+                &make_node!(root (while_statement "b") (return_statement "c")),
+                options()
+            )
+            .unwrap(),
+            vec![Statement("b".into()), Statement("c".into())]
+        );
+        assert_eq!(
+            deserialize_with_options::<Statement>(&make_node!(statement_list "a"), options())
+                .unwrap_err(),
+            DeserializeError::node_type("statement", "statement_list")
+                .at(0..1)
+                .with_path(vec!["statement_list"])
+        );
+        assert_eq!(
+            deserialize::<Statement>(&make_node!(if_statement "a")).unwrap_err(),
+            DeserializeError::node_type("statement", "if_statement")
+                .at(0..1)
+                .with_path(vec!["if_statement"])
+        );
+
+        // An alternative that matches only a prefix doesn't hide a longer one
+        let options = FromNodeOptions::new()
+            .kind_pattern("statement", regex::Regex::new(r"if|if_statement").unwrap());
+        assert_eq!(
+            deserialize_with_options::<Statement>(&make_node!(if_statement "a"), options).unwrap(),
+            Statement("a".into())
+        );
+    }
+
+    #[test]
+    fn test_text_normalization() {
+        let lowercase = || FromNodeOptions::new().text_normalization(TextNormalization::Lowercase);
+
+        assert_eq!(
+            deserialize_with_options::<bool>(&make_node!(root "TRUE"), lowercase()).unwrap(),
+            true
+        );
+        assert_eq!(
+            deserialize_with_options::<bool>(&make_node!(root "False"), lowercase()).unwrap(),
+            false
+        );
+        assert_err!(
+            bool,
+            (root "TRUE"),
+            DeserializeError::parse_bool("TRUE".parse::<bool>().unwrap_err(), "TRUE", "root")
+        );
+
+        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
+        #[serde(rename_all = "snake_case")]
+        enum Keyword {
+            Select,
+            From,
+        }
+        assert_eq!(
+            deserialize_with_options::<Keyword>(&make_node!(SELECT), lowercase()).unwrap(),
+            Keyword::Select
+        );
+
+        let strip_underscore = FromNodeOptions::new().text_normalization(
+            TextNormalization::Custom(Rc::new(|s: &str| s.replace('_', ""))),
+        );
+        assert_eq!(
+            deserialize_with_options::<u32>(&make_node!(root "1_000"), strip_underscore).unwrap(),
+            1000
+        );
+    }
+
+    #[test]
+    fn test_trim_before_parse() {
+        let trim = || FromNodeOptions::new().trim_before_parse(true);
+
+        assert_eq!(
+            deserialize_with_options::<u32>(&make_node!(root " 123 "), trim()).unwrap(),
+            123
+        );
+        assert_eq!(
+            deserialize_with_options::<f64>(&make_node!(root "\n1.5\n"), trim()).unwrap(),
+            1.5
+        );
+        assert_eq!(
+            deserialize_with_options::<bool>(&make_node!(root " true"), trim()).unwrap(),
+            true
+        );
+        assert_err!(
+            u32,
+            (root " 123 "),
+            DeserializeError::parse_int(" 123 ".parse::<u32>().unwrap_err(), " 123 ", "root")
+        );
+        assert_eq!(
+            deserialize_with_options::<u32>(&make_node!(root "12 3"), trim()).unwrap_err(),
+            DeserializeError::parse_int("12 3".parse::<u32>().unwrap_err(), "12 3", "root")
+                .at(0..4)
+        );
+    }
+
+    #[test]
+    fn test_radix_prefixes() {
+        let radix = || FromNodeOptions::new().radix_prefixes(true);
+
+        assert_eq!(
+            deserialize_with_options::<u32>(&make_node!(root "0xFF_FF"), radix()).unwrap(),
+            0xFFFF
+        );
+        assert_eq!(
+            deserialize_with_options::<u32>(&make_node!(root "0o17"), radix()).unwrap(),
+            0o17
+        );
+        assert_eq!(
+            deserialize_with_options::<u8>(&make_node!(root "0b1010"), radix()).unwrap(),
+            0b1010
+        );
+        assert_eq!(
+            deserialize_with_options::<i32>(&make_node!(root "-0x10"), radix()).unwrap(),
+            -0x10
+        );
+        assert_eq!(
+            deserialize_with_options::<u32>(&make_node!(root "1_000"), radix()).unwrap(),
+            1000
+        );
+        assert_eq!(
+            deserialize_with_options::<u8>(&make_node!(root "0x100"), radix()).unwrap_err(),
+            DeserializeError::parse_int(
+                u8::from_str_radix("100", 16).unwrap_err(),
+                "0x100",
+                "root"
+            )
+            .at(0..5)
+        );
+        assert_err!(
+            u32,
+            (root "0xFF"),
+            DeserializeError::parse_int("0xFF".parse::<u32>().unwrap_err(), "0xFF", "root")
+        );
+
+        // A sign is allowed only before the prefix
+        let invalid_digit = "-".parse::<i32>().unwrap_err();
+        for text in ["0x-5", "0x+5", "-0x-5", "+0o+7"] {
+            assert_eq!(
+                deserialize_with_options::<i32>(&DummyNode::new("root", text, vec![]), radix())
+                    .unwrap_err(),
+                DeserializeError::parse_int(invalid_digit.clone(), text, "root").at(0..text.len())
+            );
+        }
+        assert_eq!(
+            deserialize_with_options::<i32>(&make_node!(root "+0x5"), radix()).unwrap(),
+            5
+        );
+        assert_eq!(
+            deserialize_with_options::<i8>(&make_node!(root "-0x80"), radix()).unwrap(),
+            i8::MIN
+        );
+    }
+
+    #[test]
+    fn test_allow_digit_separators() {
+        let separators = || FromNodeOptions::new().allow_digit_separators(true);
+
+        assert_eq!(
+            deserialize_with_options::<u32>(&make_node!(root "1_000_000"), separators()).unwrap(),
+            1_000_000
+        );
+        assert_eq!(
+            deserialize_with_options::<f64>(&make_node!(root "1.25_5"), separators()).unwrap(),
+            1.255
+        );
+        for text in ["_1", "1_", "1__2"] {
+            assert_eq!(
+                deserialize_with_options::<u32>(
+                    &DummyNode::new("root", text, vec![]),
+                    separators()
+                )
+                .unwrap_err(),
+                DeserializeError::parse_int(text.parse::<u32>().unwrap_err(), text, "root")
+                    .at(0..text.len())
+            );
+        }
+        assert_eq!(
+            deserialize_with_options::<f64>(&make_node!(root "1._5"), separators()).unwrap_err(),
+            DeserializeError::parse_float("1._5".parse::<f64>().unwrap_err(), "1._5", "root")
+                .at(0..4)
+        );
+        assert_err!(
+            u32,
+            (root "1_000"),
+            DeserializeError::parse_int("1_000".parse::<u32>().unwrap_err(), "1_000", "root")
+        );
+    }
+
+    #[test]
+    fn test_bool_literals() {
+        let literals =
+            || FromNodeOptions::new().bool_literals(&["yes", "on", "1"], &["no", "off", "0"]);
+
+        for (text, expected) in [
+            ("yes", true),
+            ("no", false),
+            ("on", true),
+            ("off", false),
+            ("1", true),
+            ("0", false),
+            ("true", true),
+        ] {
+            assert_eq!(
+                deserialize_with_options::<bool>(&DummyNode::new("root", text, vec![]), literals())
+                    .unwrap(),
+                expected
+            );
+        }
+        assert_eq!(
+            deserialize_with_options::<bool>(&make_node!(root "maybe"), literals()).unwrap_err(),
+            DeserializeError::parse_bool("maybe".parse::<bool>().unwrap_err(), "maybe", "root")
+                .at(0..5)
+        );
+        assert_err!(
+            bool,
+            (root "yes"),
+            DeserializeError::parse_bool("yes".parse::<bool>().unwrap_err(), "yes", "root")
+        );
+    }
+
+    #[test]
+    fn test_float_special_values() {
+        for (text, expected) in [
+            ("inf", f64::INFINITY),
+            ("-inf", f64::NEG_INFINITY),
+            ("Infinity", f64::INFINITY),
+            ("-Infinity", f64::NEG_INFINITY),
+            ("INF", f64::INFINITY),
+        ] {
+            assert_eq!(
+                deserialize::<f64>(&DummyNode::new("root", text, vec![])).unwrap(),
+                expected
+            );
+        }
+        for text in ["nan", "NaN", "NAN", "-nan"] {
+            assert!(deserialize::<f64>(&DummyNode::new("root", text, vec![]))
+                .unwrap()
+                .is_nan());
+        }
+        assert!(deserialize::<f32>(&make_node!(root "NaN"))
+            .unwrap()
+            .is_nan());
+        assert_err!(
+            f64,
+            (root "infnan"),
+            DeserializeError::parse_float("infnan".parse::<f64>().unwrap_err(), "infnan", "root")
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_json() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename_all = "snake_case")]
+        #[serde(rename = "document")]
+        struct Document(Vec<Value>);
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename_all = "snake_case")]
+        enum Value {
+            Object(Vec<Pair>),
+            Number(String),
+            Array(Vec<Value>),
+            String(Option<StringContent>),
+            Null,
+        }
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "pair", rename_all = "snake_case")]
+        struct Pair {
+            key: StringContainer,
+            value: Box<Value>,
+        }
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "string")]
+        struct StringContainer(Option<StringContent>);
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "string_content")]
+        struct StringContent(String);
+
+        let src = r#"
+        {
+            "foo": 123,
+            "bar": 4.5,
+            "baz": [null, 0, ""]
+        }
+        {}
+        "#;
+
+        let tree = parser.parse(src, None).unwrap();
+        // show_node(&TsNodeImpl { node: tree.root_node(), src, });
+        let ast: Document = from_tree(&tree, src).unwrap();
+
+        assert_eq!(
+            ast,
+            Document(vec![
+                Value::Object(vec![
+                    Pair {
+                        key: StringContainer(Some(StringContent("foo".into()))),
+                        value: Box::new(Value::Number("123".into()))
+                    },
+                    Pair {
+                        key: StringContainer(Some(StringContent("bar".into()))),
+                        value: Box::new(Value::Number("4.5".into()))
+                    },
+                    Pair {
+                        key: StringContainer(Some(StringContent("baz".into()))),
+                        value: Box::new(Value::Array(vec![
+                            Value::Null,
+                            Value::Number("0".into()),
+                            Value::String(None),
+                        ]))
+                    }
+                ]),
+                Value::Object(vec![]),
+            ])
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_from_tree_check() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "document")]
+        struct Document(Vec<ChildKinds>);
+
+        let src = "[1, 2]";
+        let tree = parser.parse(src, None).unwrap();
+        assert_eq!(
+            from_tree::<Document>(&tree, src).unwrap(),
+            Document(vec![ChildKinds(vec!["number".into(), "number".into()])])
+        );
+        assert_eq!(
+            from_tree_with_check::<Document>(&tree, src, true).unwrap(),
+            from_tree::<Document>(&tree, src).unwrap()
+        );
+
+        let src = "[1, 2 3]";
+        let tree = parser.parse(src, None).unwrap();
+        assert!(matches!(
+            from_tree::<Document>(&tree, src).unwrap_err(),
+            DeserializeError::TreeSitterError(_)
+        ));
+        assert!(matches!(
+            from_node::<Document>(tree.root_node(), src).unwrap_err(),
+            DeserializeError::TreeSitterError(_)
+        ));
+        assert!(from_tree_with_check::<Document>(&tree, src, false).is_ok());
+        assert!(from_node_with_check::<Document>(tree.root_node(), src, false).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_syntax_error() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        let point = |row, column| Point { row, column };
+
+        let src = "[1,\n 2 3]";
+        let tree = parser.parse(src, None).unwrap();
+        let err = from_tree::<Vec<String>>(&tree, src).unwrap_err();
+        assert_eq!(
+            err,
+            DeserializeError::TreeSitterError(vec![SyntaxError {
+                kind: SyntaxErrorKind::Error,
+                range: 7..8,
+                start: point(1, 3),
+                end: point(1, 4),
+                src: "3".into(),
+            }])
+        );
+        assert_eq!(
+            err.to_string(),
+            "Tree-sitter node contain error(s): [ERROR at 2:4: `3`]"
+        );
+        assert_eq!(err.range(), Some(7..8));
+
+        let src = r#"{ "a": 1"#;
+        let tree = parser.parse(src, None).unwrap();
+        let err = from_tree::<Vec<String>>(&tree, src).unwrap_err();
+        assert_eq!(
+            err,
+            DeserializeError::TreeSitterError(vec![SyntaxError {
+                kind: SyntaxErrorKind::Missing("}"),
+                range: 8..8,
+                start: point(0, 8),
+                end: point(0, 8),
+                src: "".into(),
+            }])
+        );
+        assert_eq!(
+            err.to_string(),
+            "Tree-sitter node contain error(s): [MISSING `}` at 1:9]"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_collect_errors() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        let src = "[1, 2 3]";
+        let tree = parser.parse(src, None).unwrap();
+        let errors = collect_errors(tree.root_node(), src);
+        assert_eq!(
+            errors.iter().map(|e| e.range.clone()).collect::<Vec<_>>(),
+            vec![6..7]
+        );
+
+        // Deserialization can go on after reporting them
+        let options = FromNodeOptions::new().skip_error_nodes(true);
+        assert_eq!(
+            from_tree_with_options::<Vec<Vec<u32>>>(&tree, src, options).unwrap(),
+            vec![vec![1, 2]]
+        );
+
+        let src = "[1, 2]";
+        let tree = parser.parse(src, None).unwrap();
+        assert_eq!(collect_errors(tree.root_node(), src), vec![]);
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_error_range() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "array")]
+        struct Array(u32, bool);
+
+        let src = "[1, 2]";
+        let tree = parser.parse(src, None).unwrap();
+        let array = tree.root_node().named_child(0).unwrap();
+        let err = from_node::<Array>(array, src).unwrap_err();
+        assert_eq!(err.range(), Some(4..5));
+        assert_eq!(
+            err.without_range(),
+            &DeserializeError::parse_bool("2".parse::<bool>().unwrap_err(), "2", "number")
+        );
+        assert_eq!(
+            err.to_string(),
+            "Failed to parse `2`(node kind `number`) as bool: provided string was not `true` or `false` at bytes 4..5"
+        );
+
+        let err = from_node::<Array>(tree.root_node(), src).unwrap_err();
+        assert_eq!(err.range(), Some(0..6));
+        assert_eq!(
+            err.without_range(),
+            &DeserializeError::node_type("array", "document")
+        );
+
+        assert_eq!(DeserializeError::child_length(1, 2).range(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_error_path() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "document")]
+        struct Document(Object);
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "object")]
+        struct Object(Vec<Pair>);
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "pair")]
+        struct Pair {
+            value: Number,
+        }
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "number")]
+        struct Number(u32);
+
+        let src = r#"{"a": 1, "b": "x"}"#;
+        let tree = parser.parse(src, None).unwrap();
+        let err = from_node::<Document>(tree.root_node(), src).unwrap_err();
+        assert_eq!(err.path(), &["document", "object", "pair", "string"]);
+        assert_eq!(
+            err.to_string(),
+            "in field `value`: node type expected `number`, actual `string` at bytes 14..17 in `document > object > pair > string`"
+        );
+
+        let err = from_node::<Object>(tree.root_node(), src).unwrap_err();
+        assert_eq!(err.path(), &["document"]);
+        assert_eq!(DeserializeError::child_length(1, 2).path(), &[] as &[&str]);
+    }
+
+    #[test]
+    fn test_serde_path() {
+        use serde::de::{DeserializeSeed, Deserializer, SeqAccess, Visitor};
+        use std::cell::RefCell;
+
+        // A minimal `serde_path_to_error`: records the path of the failed value while the error
+        // propagates. Struct fields are visited as a sequence, so they are reported by index.
+        struct Track<'p, T> {
+            inner: T,
+            path: &'p RefCell<Vec<String>>,
+        }
+        impl<'p, T> Track<'p, T> {
+            fn wrap<U>(&self, inner: U) -> Track<'p, U> {
+                Track {
+                    inner,
+                    path: self.path,
+                }
+            }
+        }
+
+        macro_rules! forward_deserialize {
+            ($($method:ident)*) => {$(
+                fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+                    let visitor = self.wrap(visitor);
+                    self.inner.$method(visitor)
+                }
+            )*};
+        }
+        impl<'p, 'de, D: Deserializer<'de>> Deserializer<'de> for Track<'p, D> {
+            type Error = D::Error;
+            forward_deserialize!(
+                deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32
+                deserialize_i64 deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+                deserialize_f32 deserialize_f64 deserialize_char deserialize_str
+                deserialize_string deserialize_bytes deserialize_byte_buf deserialize_option
+                deserialize_unit deserialize_seq deserialize_map deserialize_identifier
+                deserialize_ignored_any
+            );
+            fn deserialize_unit_struct<V: Visitor<'de>>(
+                self,
+                name: &'static str,
+                visitor: V,
+            ) -> Result<V::Value, D::Error> {
+                let visitor = self.wrap(visitor);
+                self.inner.deserialize_unit_struct(name, visitor)
+            }
+            fn deserialize_newtype_struct<V: Visitor<'de>>(
+                self,
+                name: &'static str,
+                visitor: V,
+            ) -> Result<V::Value, D::Error> {
+                let visitor = self.wrap(visitor);
+                self.inner.deserialize_newtype_struct(name, visitor)
+            }
+            fn deserialize_tuple<V: Visitor<'de>>(
+                self,
+                len: usize,
+                visitor: V,
+            ) -> Result<V::Value, D::Error> {
+                let visitor = self.wrap(visitor);
+                self.inner.deserialize_tuple(len, visitor)
+            }
+            fn deserialize_tuple_struct<V: Visitor<'de>>(
+                self,
+                name: &'static str,
+                len: usize,
+                visitor: V,
+            ) -> Result<V::Value, D::Error> {
+                let visitor = self.wrap(visitor);
+                self.inner.deserialize_tuple_struct(name, len, visitor)
+            }
+            fn deserialize_struct<V: Visitor<'de>>(
+                self,
+                name: &'static str,
+                fields: &'static [&'static str],
+                visitor: V,
+            ) -> Result<V::Value, D::Error> {
+                let visitor = self.wrap(visitor);
+                self.inner.deserialize_struct(name, fields, visitor)
+            }
+            fn deserialize_enum<V: Visitor<'de>>(
+                self,
+                name: &'static str,
+                variants: &'static [&'static str],
+                visitor: V,
+            ) -> Result<V::Value, D::Error> {
+                let visitor = self.wrap(visitor);
+                self.inner.deserialize_enum(name, variants, visitor)
+            }
+        }
+
+        macro_rules! forward_visit {
+            ($($method:ident($t:ty))*) => {$(
+                fn $method<E: serde::de::Error>(self, v: $t) -> Result<V::Value, E> {
+                    self.inner.$method(v)
+                }
+            )*};
+        }
+        impl<'p, 'de, V: Visitor<'de>> Visitor<'de> for Track<'p, V> {
+            type Value = V::Value;
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                self.inner.expecting(f)
+            }
+            forward_visit!(
+                visit_bool(bool) visit_i64(i64) visit_u32(u32) visit_u64(u64)
+                visit_str(&str) visit_borrowed_str(&'de str) visit_string(String)
+            );
+            fn visit_newtype_struct<D: Deserializer<'de>>(
+                self,
+                deserializer: D,
+            ) -> Result<V::Value, D::Error> {
+                let deserializer = self.wrap(deserializer);
+                self.inner.visit_newtype_struct(deserializer)
+            }
+            fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+                let seq = self.wrap((seq, 0));
+                self.inner.visit_seq(seq)
+            }
+        }
+        impl<'p, 'de, A: SeqAccess<'de>> SeqAccess<'de> for Track<'p, (A, usize)> {
+            type Error = A::Error;
+            fn next_element_seed<S: DeserializeSeed<'de>>(
+                &mut self,
+                seed: S,
+            ) -> Result<Option<S::Value>, A::Error> {
+                let index = self.inner.1;
+                self.inner.1 += 1;
+                let seed = self.wrap(seed);
+                self.inner
+                    .0
+                    .next_element_seed(seed)
+                    .inspect_err(|_| self.path.borrow_mut().insert(0, format!("[{index}]")))
+            }
+        }
+        impl<'p, 'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for Track<'p, S> {
+            type Value = S::Value;
+            fn deserialize<D: Deserializer<'de>>(
+                self,
+                deserializer: D,
+            ) -> Result<S::Value, D::Error> {
+                let deserializer = self.wrap(deserializer);
+                self.inner.deserialize(deserializer)
+            }
+        }
+
+        #[allow(dead_code)]
+        #[derive(Debug, serde::Deserialize)]
+        #[serde(rename = "root")]
+        struct Root {
+            name: String,
+            foo: Foo,
+        }
+        #[allow(dead_code)]
+        #[derive(Debug, serde::Deserialize)]
+        #[serde(rename = "foo")]
+        struct Foo {
+            bar: Vec<u32>,
+        }
+
+        let node = make_node!(root
+            name: (identifier "x")
+            foo: (foo bar: (int "1") bar: (int "2") bar: (int "x"))
+        );
+        let path = RefCell::new(vec![]);
+        let seed = Track {
+            inner: std::marker::PhantomData::<Root>,
+            path: &path,
+        };
+        let de = crate::deserializer::NodeDeserializer::enter(
+            DummyRef::root(&node),
+            Rc::new(context::Context::new(FromNodeOptions::new())),
+        )
+        .unwrap();
+        assert!(seed.deserialize(de).is_err());
+        // `foo` is the second field of `Root`, and `bar` is the first field of `Foo`
+        assert_eq!(path.into_inner().concat(), "[1][0][2]");
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_iter_from_tree() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "number")]
+        struct Number(u32);
+
+        let src = "1 2 x";
+        let tree = parser.parse(src, None).unwrap();
+        let mut iter = iter_from_tree::<Number>(&tree, src, false);
+        assert_eq!(iter.next().unwrap().unwrap(), Number(1));
+        assert_eq!(iter.next().unwrap().unwrap(), Number(2));
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+
+        let mut iter = iter_from_tree::<Number>(&tree, src, true);
+        assert!(matches!(
+            iter.next().unwrap().unwrap_err(),
+            DeserializeError::TreeSitterError(_)
+        ));
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_deny_unconsumed_children() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+        let options = || FromNodeOptions::new().deny_unconsumed_children(true);
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "object")]
+        struct Object(Vec<Key>);
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "pair")]
+        struct Key {
+            key: String,
+        }
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "pair")]
+        struct Pair {
+            key: String,
+            value: u32,
+        }
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "object")]
+        struct Marker;
+
+        let src = r#"{"a": 1, "b": 2}"#;
+        let tree = parser.parse(src, None).unwrap();
+        let object = tree.root_node().named_child(0).unwrap();
+        assert_eq!(
+            from_node_with_options::<Object>(object, src, options()).unwrap_err(),
+            DeserializeError::UnconsumedChildren {
+                ranges: vec![6..7, 14..15]
+            }
+        );
+        assert_eq!(
+            from_node_with_options::<Marker>(object, src, options()).unwrap_err(),
+            DeserializeError::UnconsumedChildren {
+                ranges: vec![1..7, 9..15]
+            }
+        );
+        assert_eq!(
+            from_node_with_options::<Vec<Pair>>(object, src, options()).unwrap(),
+            vec![
+                Pair {
+                    key: r#""a""#.into(),
+                    value: 1
+                },
+                Pair {
+                    key: r#""b""#.into(),
+                    value: 2
+                }
+            ]
+        );
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename_all = "snake_case")]
+        enum Item {
+            Pair,
+        }
+        assert_eq!(
+            from_node_with_options::<Vec<Item>>(object, src, options()).unwrap_err(),
+            DeserializeError::UnconsumedChildren {
+                ranges: vec![1..4, 6..7, 9..12, 14..15]
+            }
+        );
+        // The children ignored by lenient tuples are not tracked
+        assert_eq!(
+            from_node_with_options::<Vec<(String,)>>(object, src, options().lenient_tuple(true))
+                .unwrap(),
+            vec![(r#""a""#.into(),), (r#""b""#.into(),)]
+        );
+        // Checking the kind doesn't leave the children unconsumed
+        assert_eq!(
+            from_node_with_options::<Vec<MaybeKind<Pair>>>(object, src, options()).unwrap(),
+            vec![
+                MaybeKind(Some(Pair {
+                    key: r#""a""#.into(),
+                    value: 1
+                })),
+                MaybeKind(Some(Pair {
+                    key: r#""b""#.into(),
+                    value: 2
+                }))
+            ]
+        );
+        // Not tracked by default
+        assert_eq!(
+            from_node::<Object>(object, src).unwrap(),
+            Object(vec![
+                Key {
+                    key: r#""a""#.into()
+                },
+                Key {
+                    key: r#""b""#.into()
+                }
+            ])
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_struct_text_leaf() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "pair")]
+        struct Pair<'a> {
+            #[serde(borrow)]
+            key: Key<'a>,
+            value: Number,
+        }
+
+        // `string` has a `string_content` child, that is not a field
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "string")]
+        struct Key<'a> {
+            #[serde(rename = "$text")]
+            name: &'a str,
+        }
+
+        // `number` has no children
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "number")]
+        struct Number {
+            #[serde(rename = "$text")]
+            value: u32,
+        }
+
+        let src = r#"{"a": 12}"#;
+        let tree = parser.parse(src, None).unwrap();
+        let pair = tree
+            .root_node()
+            .named_child(0)
+            .and_then(|object| object.named_child(0))
+            .unwrap();
+        assert_eq!(
+            from_node::<Pair>(pair, src).unwrap(),
+            Pair {
+                key: Key { name: r#""a""# },
+                value: Number { value: 12 }
+            }
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_field_name() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+        let src = r#"{"a": [1]}"#;
+        let tree = parser.parse(src, None).unwrap();
+        let object = tsnode::TsNodeImpl::new(tree.root_node(), src)
+            .named_child(0)
+            .unwrap();
+        let pair = object.named_children().next().unwrap();
+        assert_eq!(pair.field_name(), None);
+        assert_eq!(
+            pair.named_children()
+                .map(|c| c.field_name())
+                .collect::<Vec<_>>(),
+            vec![Some("key"), Some("value")]
+        );
+
+        // Without the parent, the field name is looked up from the tree.
+        let value = tree
+            .root_node()
+            .named_child(0)
+            .and_then(|object| object.named_child(0))
+            .and_then(|pair| pair.named_child(1))
+            .unwrap();
+        assert_eq!(
+            tsnode::TsNodeImpl::new(value, src).field_name(),
+            Some("value")
+        );
+        assert_eq!(
+            tsnode::TsNodeImpl::new(tree.root_node(), src).field_name(),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_next_sibling() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+        let src = r#"{"a": 1}"#;
+        let tree = parser.parse(src, None).unwrap();
+        let pair = tsnode::TsNodeImpl::new(tree.root_node(), src)
+            .named_child(0)
+            .and_then(|object| object.named_child(0))
+            .unwrap();
+
+        let key = pair.children_by_field_name("key").next().unwrap();
+        let value = key.next_named_sibling().unwrap();
+        assert_eq!(value.src(), "1");
+        assert_eq!(value.prev_named_sibling().map(|n| n.src()), Some(r#""a""#));
+        assert!(value.next_named_sibling().is_none());
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "pair")]
+        struct Pair {
+            key: NextSibling<u32>,
+        }
+        assert_eq!(
+            from_ts_node::<Pair, _>(pair.clone()).unwrap(),
+            Pair {
+                key: NextSibling(1)
+            }
+        );
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "pair")]
+        struct PairValue {
+            value: NextSibling<u32>,
+        }
+        assert_eq!(
+            from_ts_node::<PairValue, _>(pair).unwrap_err(),
+            DeserializeError::NoNextSibling.at(6..7)
+        );
+    }
+
+    #[test]
+    fn test_next_sibling_without_tree_sitter() {
+        let node = make_node!(pair key: (string "a") value: (number "1"));
+        let key = DummyRef::root(&node).named_child(0).unwrap();
+        let value = key.next_named_sibling().unwrap();
+        assert_eq!(value.src(), "1");
+        assert_eq!(value.prev_named_sibling().map(|n| n.src()), Some("a"));
+        assert!(value.next_named_sibling().is_none());
+        assert!(key.prev_named_sibling().is_none());
+        assert!(DummyRef::root(&node).next_named_sibling().is_none());
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "pair")]
+        struct Pair {
+            key: NextSibling<u32>,
+        }
+        assert_ok!(
+            Pair,
+            (pair key: (string "a") value: (number "1")),
+            Pair {
+                key: NextSibling(1)
+            }
+        );
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "pair")]
+        struct PairValue {
+            value: NextSibling<u32>,
+        }
+        assert_err!(
+            PairValue,
+            (pair key: (string "a") value: (number "1")),
+            DeserializeError::NoNextSibling
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_node_id() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        #[derive(Debug, serde::Deserialize)]
+        #[serde(rename = "pair")]
+        struct Pair {
+            #[serde(rename = "$span")]
+            pair: NodeId,
+            key: NextSibling<NodeId>,
+            value: NodeId,
+        }
+        #[derive(Debug, serde::Deserialize)]
+        #[serde(rename = "object")]
+        struct Object(Vec<Pair>);
+        #[derive(Debug, serde::Deserialize)]
+        #[serde(rename = "document")]
+        struct Document(Object);
+
+        let src = r#"{"a": 1}"#;
+        let tree = parser.parse(src, None).unwrap();
+        let Document(Object(pairs)) = from_tree(&tree, src).unwrap();
+        let pair = &pairs[0];
+        let root = tree.root_node();
+        let node = root.named_child(0).unwrap().named_child(0).unwrap();
+        assert_eq!(pair.pair, NodeId(node.id()));
+        assert_eq!(
+            pair.value,
+            NodeId(node.child_by_field_name("value").unwrap().id())
+        );
+        // Both fields point at the value node
+        assert_eq!(pair.key.0, pair.value);
+        assert_ne!(pair.pair, pair.value);
+    }
+
+    #[test]
+    fn test_iterators_without_length() {
+        /// A backend whose iterators don't implement `ExactSizeIterator`.
+        #[derive(Clone, Debug)]
+        struct Filtered<'a>(DummyRef<'a>);
+        impl<'a> TsNode<'a> for Filtered<'a> {
+            fn named_child(&self, index: usize) -> Option<Self> {
+                self.0.named_child(index).map(Filtered)
+            }
+            fn named_child_count(&self) -> usize {
+                self.0.named_child_count()
+            }
+            fn named_children(&self) -> impl Iterator<Item = Self> {
+                self.0.named_children().filter(|_| true).map(Filtered)
+            }
+            fn child_count(&self) -> usize {
+                self.0.child_count()
+            }
+            fn children(&self) -> impl Iterator<Item = Self> {
+                self.0.children().filter(|_| true).map(Filtered)
+            }
+            fn children_by_field_name(&self, name: &str) -> impl Iterator<Item = Self> {
+                self.0.children_by_field_name(name).map(Filtered)
+            }
+            fn field_names(&self) -> Vec<&'static str> {
+                self.0.field_names()
+            }
+            fn kind(&self) -> &'static str {
+                self.0.kind()
+            }
+            fn src(&self) -> &'a str {
+                self.0.src()
+            }
+            fn byte_range(&self) -> std::ops::Range<usize> {
+                self.0.byte_range()
+            }
+            fn start_position(&self) -> Point {
+                self.0.start_position()
+            }
+            fn end_position(&self) -> Point {
+                self.0.end_position()
+            }
+            fn id(&self) -> usize {
+                self.0.id()
+            }
+            fn is_error(&self) -> bool {
+                self.0.is_error()
+            }
+        }
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "root")]
+        struct Root {
+            items: Vec<u32>,
+            pair: Pair,
+        }
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "pair")]
+        struct Pair(u32, Option<u32>);
+
+        let node = make_node!(root
+            items: (num "1") items: (num "2")
+            pair: (pair (num "3") (opt (num "4")))
+        );
+        assert_eq!(
+            from_ts_node::<Root, _>(Filtered(DummyRef::root(&node))).unwrap(),
+            Root {
+                items: vec![1, 2],
+                pair: Pair(3, Some(4)),
+            }
+        );
+        assert_eq!(
+            from_ts_node::<(u32, u32), _>(Filtered(DummyRef::root(&node))).unwrap_err(),
+            DeserializeError::child_length(2, 3)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_from_node_bytes() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "document")]
+        struct Document<T>(T);
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "array")]
+        struct Array<T>(u32, T);
+
+        let src = b"[1, \"\xff\"]";
+        let tree = parser.parse(src, None).unwrap();
+        let root = tree.root_node();
+        assert_eq!(
+            from_node_bytes::<Document<Array<serde::de::IgnoredAny>>>(root, src).unwrap(),
+            Document(Array(1, serde::de::IgnoredAny))
+        );
+        assert_eq!(
+            from_node_bytes::<Document<Array<&[u8]>>>(root, src).unwrap(),
+            Document(Array(1, &b"\"\xff\""[..]))
+        );
+        assert_eq!(
+            from_node_bytes::<Document<Array<&str>>>(root, src).unwrap_err(),
+            DeserializeError::Utf8Error(std::str::from_utf8(&src[4..7]).unwrap_err()).at(4..7)
+        );
+
+        // Every type that reads the text reports the error, instead of reading an empty text
+        let utf8_error =
+            DeserializeError::Utf8Error(std::str::from_utf8(&src[4..7]).unwrap_err()).at(4..7);
+        assert_eq!(
+            from_node_bytes::<Document<Array<String>>>(root, src).unwrap_err(),
+            utf8_error
+        );
+        assert_eq!(
+            from_node_bytes::<Document<Array<u32>>>(root, src).unwrap_err(),
+            utf8_error
+        );
+        assert_eq!(
+            from_node_bytes::<Document<Array<bool>>>(root, src).unwrap_err(),
+            utf8_error
+        );
+        assert_eq!(
+            from_node_bytes::<Document<Array<Joined>>>(root, src).unwrap_err(),
+            utf8_error
+        );
+        assert_eq!(
+            from_node_bytes::<Document<Array<RawText>>>(root, src).unwrap_err(),
+            utf8_error
+        );
+        // `src` is only for debugging
+        let string = root.named_child(0).unwrap().named_child(1).unwrap();
+        let node = tsnode::TsNodeImpl::from_bytes(string, src);
+        assert_eq!(node.src(), "");
+        assert!(node.try_src().is_err());
+
+        // Options are honored
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "array")]
+        struct Marker;
+
+        let options = FromNodeOptions::new().deny_unconsumed_children(true);
+        assert_eq!(
+            from_node_bytes_with_options::<Document<Marker>>(root, src, options).unwrap_err(),
+            DeserializeError::UnconsumedChildren {
+                ranges: vec![1..2, 4..7]
+            }
+        );
+        let src = b"[1, \"\xff\" 2]";
+        let tree = parser.parse(src, None).unwrap();
+        let root = tree.root_node();
+        assert!(matches!(
+            from_node_bytes::<Document<Array<&[u8]>>>(root, src).unwrap_err(),
+            DeserializeError::TreeSitterError(_)
+        ));
+        let options = FromNodeOptions::new().skip_error_nodes(true);
+        assert_eq!(
+            from_node_bytes_with_options::<Document<Array<&[u8]>>>(root, src, options).unwrap(),
+            Document(Array(1, &b"\"\xff\""[..]))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_from_str() {
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "document")]
+        struct Document<'a>(#[serde(borrow)] Vec<Vec<&'a str>>);
+
+        let src = "[1, 2]";
+        assert_eq!(
+            from_str::<Document>(src, tree_sitter_json::language(), true).unwrap(),
+            Document(vec![vec!["1", "2"]])
+        );
+
+        let src = "[1, 2 3]";
+        assert!(matches!(
+            from_str::<Document>(src, tree_sitter_json::language(), true).unwrap_err(),
+            DeserializeError::TreeSitterError(_)
+        ));
+        assert!(from_str::<Document>(src, tree_sitter_json::language(), false).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_from_node_seed() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "document")]
+        struct Document(Vec<u32>);
+
+        /// Tags the value with the ID of the source file.
+        struct FileSeed(u32);
+        impl<'de> serde::de::DeserializeSeed<'de> for FileSeed {
+            type Value = (u32, Document);
+            fn deserialize<D: serde::Deserializer<'de>>(
+                self,
+                deserializer: D,
+            ) -> Result<Self::Value, D::Error> {
+                Document::deserialize(deserializer).map(|doc| (self.0, doc))
+            }
+        }
+
+        let src = "1 2";
+        let tree = parser.parse(src, None).unwrap();
+        assert_eq!(
+            from_node_seed(FileSeed(42), tree.root_node(), src, true).unwrap(),
+            (42, Document(vec![1, 2]))
+        );
+
+        let src = "1 [";
+        let tree = parser.parse(src, None).unwrap();
+        assert!(matches!(
+            from_node_seed(FileSeed(42), tree.root_node(), src, true),
+            Err(DeserializeError::TreeSitterError(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_field_from_node() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        let src = r#"{"a": 1}"#;
+        let tree = parser.parse(src, None).unwrap();
+        let pair = tree
+            .root_node()
+            .named_child(0)
+            .unwrap()
+            .named_child(0)
+            .unwrap();
+        assert_eq!(field_from_node::<&str>(pair, src, "key").unwrap(), r#""a""#);
+        assert_eq!(field_from_node::<u32>(pair, src, "value").unwrap(), 1);
+        assert_eq!(
+            field_from_node::<Option<u32>>(pair, src, "value").unwrap(),
+            Some(1)
+        );
+
+        // Absent field
+        assert_eq!(
+            field_from_node::<u32>(pair, src, "name").unwrap_err(),
+            DeserializeError::field_length("name", 1, 0)
+        );
+        assert_eq!(
+            field_from_node::<Vec<u32>>(pair, src, "name").unwrap(),
+            Vec::<u32>::new()
+        );
+
+        // Errors in the field are located
+        assert_eq!(
+            field_from_node::<bool>(pair, src, "value")
+                .unwrap_err()
+                .range(),
+            Some(6..7)
+        );
     }
 
     #[test]
-    fn test_enum() {
-        #[derive(serde::Deserialize, PartialEq, Eq, Debug)]
-        #[serde(rename_all = "snake_case")]
-        enum Value {
-            // Unit variant
-            Null,
-            // Newtype variant
-            Int(i64),
-            // Tuple variant
-            Tuple(String, i32),
-            // Struct variant
-            Struct {
-                a: u32,
-                b: Vec<String>,
-                c: Option<String>,
-            },
+    #[cfg(feature = "tree-sitter")]
+    fn test_from_node_collecting() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "number")]
+        struct Number(u32);
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "pair")]
+        struct Pair {
+            key: String,
+            value: Number,
         }
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "object")]
+        struct Object(Vec<Pair>);
 
-        // unit
+        let src = r#"{"a": 1, "b": 2}"#;
+        let tree = parser.parse(src, None).unwrap();
+        let object = tree.root_node().named_child(0).unwrap();
         assert_eq!(
-            deserialize::<Value>(&make_node!(null "foo")).unwrap(),
-            Value::Null,
+            from_node_collecting::<Object>(object, src).unwrap(),
+            Object(vec![
+                Pair {
+                    key: r#""a""#.into(),
+                    value: Number(1)
+                },
+                Pair {
+                    key: r#""b""#.into(),
+                    value: Number(2)
+                },
+            ])
         );
 
-        // newtype(ok)
+        let src = r#"{"a": 1.5, "b": 2, "c": "x"}"#;
+        let tree = parser.parse(src, None).unwrap();
+        let object = tree.root_node().named_child(0).unwrap();
         assert_eq!(
-            deserialize::<Value>(&make_node!(int "999")).unwrap(),
-            Value::Int(999),
+            from_node_collecting::<Object>(object, src).unwrap_err(),
+            vec![
+                DeserializeError::parse_int("1.5".parse::<u32>().unwrap_err(), "1.5", "number")
+                    .at(6..9),
+                DeserializeError::field_node_type("value", "number", "string")
+                    .at(24..27)
+                    .with_path(vec!["object", "pair", "string"]),
+            ]
         );
+        // Not collected by default
+        assert_eq!(
+            from_node::<Object>(object, src).unwrap_err(),
+            DeserializeError::parse_int("1.5".parse::<u32>().unwrap_err(), "1.5", "number")
+                .at(6..9)
+        );
+    }
 
-        // TODO: newtype(error)
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_deserialize_query() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "pair")]
+        struct Pair<'a> {
+            key: &'a str,
+            value: &'a str,
+        }
+
+        let src = r#"{"a": 1, "b": {"c": null}}"#;
+        let tree = parser.parse(src, None).unwrap();
+        let query = tree_sitter::Query::new(
+            tree_sitter_json::language(),
+            "(pair key: (string) @key) @pair",
+        )
+        .unwrap();
 
-        // tuple(ok)
         assert_eq!(
-            deserialize::<Value>(&make_node!(tuple "999" (c1 "foo") (c2 "333"))).unwrap(),
-            Value::Tuple("foo".into(), 333),
+            deserialize_query::<Pair>(&tree, src, &query, "pair").unwrap(),
+            vec![
+                Pair {
+                    key: r#""a""#,
+                    value: "1"
+                },
+                Pair {
+                    key: r#""b""#,
+                    value: r#"{"c": null}"#
+                },
+                Pair {
+                    key: r#""c""#,
+                    value: "null"
+                },
+            ]
+        );
+        assert_eq!(
+            deserialize_query::<String>(&tree, src, &query, "key").unwrap(),
+            vec![r#""a""#, r#""b""#, r#""c""#]
+        );
+        assert_eq!(
+            deserialize_query::<Pair>(&tree, src, &query, "unknown").unwrap_err(),
+            DeserializeError::UnknownCapture("unknown".into())
         );
 
-        // tuple(error: child count)
+        // collecting
+        let src = r#"{"a": 1, "b": "x", "c": 3, "d": true}"#;
+        let tree = parser.parse(src, None).unwrap();
+        let query =
+            tree_sitter::Query::new(tree_sitter_json::language(), "(pair value: (_) @value)")
+                .unwrap();
+        assert!(deserialize_query::<u32>(&tree, src, &query, "value").is_err());
+        let (values, errors) = deserialize_query_collecting::<u32>(&tree, src, &query, "value");
+        assert_eq!(values, vec![1, 3]);
         assert_eq!(
-            deserialize::<Value>(&make_node!(tuple "999" (c1 "foo"))).unwrap_err(),
-            DeserializeError::child_length(2, 1),
+            errors.iter().map(|e| e.range()).collect::<Vec<_>>(),
+            vec![Some(14..17), Some(32..36)]
+        );
+        assert_eq!(
+            deserialize_query_collecting::<u32>(&tree, src, &query, "unknown"),
+            (
+                vec![],
+                vec![DeserializeError::UnknownCapture("unknown".into())]
+            )
         );
+    }
 
-        // tuple(error: type error)
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_from_query_match() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
+        struct Captures<'a> {
+            key: &'a str,
+            value: Option<u32>,
+            item: Vec<u32>,
+        }
+
+        let src = r#"{"a": [1, 2], "b": 3}"#;
+        let tree = parser.parse(src, None).unwrap();
+        let query = tree_sitter::Query::new(
+            tree_sitter_json::language(),
+            r#"
+            (pair key: (string) @key value: (array (number) @item (number) @item))
+            (pair key: (string) @key value: (number) @value)
+            "#,
+        )
+        .unwrap();
+        let mut cursor = tree_sitter::QueryCursor::new();
+        let captures = cursor
+            .matches(&query, tree.root_node(), src.as_bytes())
+            .map(|m| from_query_match::<Captures>(&m, &query, src).unwrap())
+            .collect::<Vec<_>>();
         assert_eq!(
-            deserialize::<Value>(&make_node!(tuple "999" (c1 "foo") (c2 "not_a_number")))
-                .unwrap_err(),
-            DeserializeError::ParseIntError("not_a_number".parse::<i32>().unwrap_err())
+            captures,
+            vec![
+                Captures {
+                    key: r#""a""#,
+                    value: None,
+                    item: vec![1, 2],
+                },
+                Captures {
+                    key: r#""b""#,
+                    value: Some(3),
+                    item: vec![],
+                },
+            ]
         );
 
-        // struct(ok: b = [...], c = None)
+        #[derive(Debug, serde::Deserialize)]
+        struct Single {
+            #[allow(dead_code)]
+            item: u32,
+        }
+        let m = cursor
+            .matches(&query, tree.root_node(), src.as_bytes())
+            .next()
+            .unwrap();
         assert_eq!(
-            deserialize::<Value>(&make_node!(struct ""
-                a: (foo "123")
-                b: (bar "a")
-                b: (bar "b")
-                (baz)
-                b: (bar "c")
-            ))
-            .unwrap(),
-            Value::Struct {
-                a: 123,
-                b: ["a", "b", "c"].into_iter().map(|x| x.to_owned()).collect(),
-                c: None,
-            }
+            from_query_match::<Single>(&m, &query, src).unwrap_err(),
+            DeserializeError::field_length("item", 1, 2)
         );
+    }
 
-        // struct(ok: b = [], c = "foo")
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_deserialize_by_child_kind() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        #[derive(Debug, PartialEq, Eq)]
+        enum Top<'a> {
+            Number(u32),
+            Items(Vec<&'a str>),
+        }
+        fn number<'a>(
+            node: tree_sitter::Node<'a>,
+            src: &'a str,
+        ) -> Result<Top<'a>, DeserializeError> {
+            from_node(node, src).map(Top::Number)
+        }
+        fn array<'a>(
+            node: tree_sitter::Node<'a>,
+            src: &'a str,
+        ) -> Result<Top<'a>, DeserializeError> {
+            from_node(node, src).map(Top::Items)
+        }
+        let table: &[(&str, NodeConstructor<Top>)] = &[("number", number), ("array", array)];
+
+        let mut parse = |src| parser.parse(src, None).unwrap();
+
+        let src = "123";
+        let tree = parse(src);
         assert_eq!(
-            deserialize::<Value>(&make_node!(struct ""
-                a: (foo "123")
-                (baz)
-                c: (foo "foo")
-            ))
-            .unwrap(),
-            Value::Struct {
-                a: 123,
-                b: vec![],
-                c: Some("foo".into()),
-            }
+            deserialize_by_child_kind(tree.root_node(), src, table).unwrap(),
+            Top::Number(123)
         );
-        // struct(error: missing a)
+
+        let src = "[1, true]";
+        let tree = parse(src);
         assert_eq!(
-            deserialize::<Value>(&make_node!(struct ""
-                b: (foo "123")
-                (baz)
-            ))
-            .unwrap_err(),
-            DeserializeError::field_length("a", 1, 0)
+            deserialize_by_child_kind(tree.root_node(), src, table).unwrap(),
+            Top::Items(vec!["1", "true"])
         );
-        // struct(error: option field length > 1)
+
+        let src = "null";
+        let tree = parse(src);
         assert_eq!(
-            deserialize::<Value>(&make_node!(struct ""
-                a: (foo "123")
-                (baz)
-                c: (foo "foo")
-                c: (foo "foo")
-            ))
-            .unwrap_err(),
-            DeserializeError::field_length("c", 1, 2)
+            deserialize_by_child_kind(tree.root_node(), src, table).unwrap_err(),
+            DeserializeError::node_type("number | array", "null")
         );
-        // error: unknown variant
-        assert!(deserialize::<Value>(&make_node!(unknown ""
-            a: (foo "123")
-            (baz)
-            c: (foo "foo")
-            c: (foo "foo")
-        ))
-        .is_err());
     }
 
     #[test]
-    fn test_json() {
+    #[cfg(feature = "tree-sitter")]
+    fn test_from_tree_traced() {
         let mut parser = tree_sitter::Parser::new();
         parser.set_language(tree_sitter_json::language()).unwrap();
 
         #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
-        #[serde(rename_all = "snake_case")]
         #[serde(rename = "document")]
-        struct Document(Vec<Value>);
+        struct Document(Object);
 
         #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
-        #[serde(rename_all = "snake_case")]
-        enum Value {
-            Object(Vec<Pair>),
-            Number(String),
-            Array(Vec<Value>),
-            String(Option<StringContent>),
-            Null,
-        }
+        #[serde(rename = "object")]
+        struct Object(Vec<Pair>);
 
         #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
-        #[serde(rename = "pair", rename_all = "snake_case")]
+        #[serde(rename = "pair")]
         struct Pair {
-            key: StringContainer,
-            value: Box<Value>,
+            key: String,
+            value: u32,
         }
 
+        let src = r#"{"a": 1}"#;
+        let tree = parser.parse(src, None).unwrap();
+        let (result, trace) = from_tree_traced::<Document>(&tree, src);
+        assert_eq!(
+            result.unwrap(),
+            Document(Object(vec![Pair {
+                key: r#""a""#.into(),
+                value: 1
+            }]))
+        );
+        assert_eq!(
+            trace,
+            vec!["document", "object", "pair", "string", "number"]
+        );
+
+        let src = r#"{"a": true}"#;
+        let tree = parser.parse(src, None).unwrap();
+        let (result, trace) = from_tree_traced::<Document>(&tree, src);
+        assert!(result.is_err());
+        assert_eq!(trace, vec!["document", "object", "pair", "string", "true"]);
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_from_tree_cached() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
         #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
-        #[serde(rename = "string")]
-        struct StringContainer(Option<StringContent>);
+        #[serde(rename = "document")]
+        struct Document(Array);
 
         #[derive(Debug, PartialEq, Eq, serde::Deserialize)]
-        #[serde(rename = "string_content")]
-        struct StringContent(String);
+        #[serde(rename = "array")]
+        struct Array(Vec<Cached<Object>>);
 
-        let src = r#"
-        {
-            "foo": 123,
-            "bar": 4.5,
-            "baz": [null, 0, ""]
+        #[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "object")]
+        struct Object(Vec<Pair>);
+
+        #[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+        #[serde(rename = "pair")]
+        struct Pair {
+            key: String,
+            value: String,
         }
-        {}
-        "#;
+        let object = |key: &str, value: &str| {
+            Cached(Object(vec![Pair {
+                key: format!(r#""{key}""#),
+                value: value.into(),
+            }]))
+        };
+
+        let mut cache = DeserializeCache::new();
+
+        let src = r#"[{"a": 1}, {"b": 2}]"#;
+        let mut tree = parser.parse(src, None).unwrap();
+        let doc: Document = from_tree_cached(&tree, src, &mut cache).unwrap();
+        assert_eq!(
+            doc,
+            Document(Array(vec![object("a", "1"), object("b", "2")]))
+        );
+        assert_eq!(cache.hits(), 0);
+        assert_eq!(cache.len(), 2);
 
+        // Edit `2` to `3`
+        let new_src = r#"[{"a": 1}, {"b": 3}]"#;
+        tree.edit(&tree_sitter::InputEdit {
+            start_byte: 17,
+            old_end_byte: 18,
+            new_end_byte: 18,
+            start_position: Point::new(0, 17),
+            old_end_position: Point::new(0, 18),
+            new_end_position: Point::new(0, 18),
+        });
+        let new_tree = parser.parse(new_src, Some(&tree)).unwrap();
+        let doc: Document = from_tree_cached(&new_tree, new_src, &mut cache).unwrap();
+        assert_eq!(
+            doc,
+            Document(Array(vec![object("a", "1"), object("b", "3")]))
+        );
+        assert_eq!(cache.hits(), 1);
+        assert_eq!(cache.len(), 2);
+
+        // Without a cache, `Cached` is transparent
+        let doc: Document = from_tree(&new_tree, new_src).unwrap();
+        assert_eq!(
+            doc,
+            Document(Array(vec![object("a", "1"), object("b", "3")]))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "tree-sitter")]
+    fn test_try_all() {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(tree_sitter_json::language()).unwrap();
+
+        #[derive(Debug, PartialEq, Eq)]
+        enum Value {
+            Int(i32),
+            Bool(bool),
+            Text(String),
+        }
+        fn int<'a>(node: tree_sitter::Node<'a>, src: &'a str) -> Result<Value, DeserializeError> {
+            from_node(node, src).map(Value::Int)
+        }
+        fn bool<'a>(node: tree_sitter::Node<'a>, src: &'a str) -> Result<Value, DeserializeError> {
+            from_node(node, src).map(Value::Bool)
+        }
+        fn text<'a>(node: tree_sitter::Node<'a>, src: &'a str) -> Result<Value, DeserializeError> {
+            from_node(node, src).map(Value::Text)
+        }
+
+        let src = "true";
         let tree = parser.parse(src, None).unwrap();
-        // show_node(&TsNodeImpl { node: tree.root_node(), src, });
-        let ast: Document = from_tree(&tree, src, true).unwrap();
+        let node = tree.root_node().named_child(0).unwrap();
+        let alternatives: [NodeConstructor<Value>; 3] = [int as _, bool as _, text as _];
+        assert_eq!(
+            try_all(node, src, &alternatives).unwrap(),
+            Value::Bool(true)
+        );
 
+        let alternatives: [NodeConstructor<Value>; 2] = [int as _, int as _];
+        let err = try_all(node, src, &alternatives).unwrap_err();
+        let int_err = || {
+            DeserializeError::parse_int("true".parse::<i32>().unwrap_err(), "true", "true").at(0..4)
+        };
         assert_eq!(
-            ast,
-            Document(vec![
-                Value::Object(vec![
-                    Pair {
-                        key: StringContainer(Some(StringContent("foo".into()))),
-                        value: Box::new(Value::Number("123".into()))
-                    },
-                    Pair {
-                        key: StringContainer(Some(StringContent("bar".into()))),
-                        value: Box::new(Value::Number("4.5".into()))
-                    },
-                    Pair {
-                        key: StringContainer(Some(StringContent("baz".into()))),
-                        value: Box::new(Value::Array(vec![
-                            Value::Null,
-                            Value::Number("0".into()),
-                            Value::String(None),
-                        ]))
-                    }
-                ]),
-                Value::Object(vec![]),
-            ])
+            err,
+            DeserializeError::AllAlternativesFailed(vec![int_err(), int_err()])
+        );
+        assert_eq!(
+            err.to_string(),
+            "All alternatives failed: [Failed to parse `true`(node kind `true`) as integer: invalid digit found in string at bytes 0..4; Failed to parse `true`(node kind `true`) as integer: invalid digit found in string at bytes 0..4]"
+        );
+    }
+
+    #[test]
+    fn test_describe() {
+        #[allow(dead_code)]
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "document")]
+        struct Document(Vec<Value>);
+
+        #[allow(dead_code)]
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "snake_case")]
+        enum Value {
+            Object(Vec<Pair>),
+            Number(u32),
+            Null,
+        }
+
+        #[allow(dead_code)]
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "pair")]
+        struct Pair {
+            #[serde(rename = "$span")]
+            span: Span,
+            key: (String,),
+            value: Option<Box<Value>>,
+            nested: Vec<Pair>,
+            args: (String, Vec<u32>),
+            id: i128,
+            size: u128,
+        }
+
+        assert_eq!(
+            describe::<Document>(),
+            [
+                "node `document`",
+                "  named children",
+                "    each: one of kinds `object`, `number`, `null`",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            describe::<Pair>(),
+            [
+                "node `pair` with fields",
+                "  `$span` of the node itself: Span",
+                "  field `key`: 1 named children",
+                "    child 0: text",
+                "  field `value`: optional",
+                "    one of kinds `object`, `number`, `null`",
+                "  field `nested`: named children",
+                "    each: node `pair` with fields (recursive)",
+                "  field `args`: 1 or more named children",
+                "    child 0: text",
+                "    child 1: remaining named children",
+                "      each: integer",
+                "  field `id`: integer",
+                "  field `size`: integer",
+            ]
+            .join("\n")
         );
     }
 }
@@ -0,0 +1,398 @@
+use std::borrow::Cow;
+use std::ops::Range;
+use std::rc::Rc;
+
+use crate::tsnode::TsNode;
+
+/// Normalization applied to node text before parsing primitives and matching enum variants.
+#[derive(Clone, Default)]
+pub enum TextNormalization {
+    #[default]
+    None,
+    Lowercase,
+    Uppercase,
+    Custom(Rc<dyn Fn(&str) -> String>),
+}
+
+impl std::fmt::Debug for TextNormalization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "None"),
+            Self::Lowercase => write!(f, "Lowercase"),
+            Self::Uppercase => write!(f, "Uppercase"),
+            Self::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+impl TextNormalization {
+    pub(crate) fn apply<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        match self {
+            Self::None => Cow::Borrowed(text),
+            Self::Lowercase => Cow::Owned(text.to_lowercase()),
+            Self::Uppercase => Cow::Owned(text.to_uppercase()),
+            Self::Custom(f) => Cow::Owned(f(text)),
+        }
+    }
+}
+
+type IgnoredChildFn = dyn Fn(&str, Range<usize>);
+
+/// Callback for [`FromNodeOptions::on_ignored_child`].
+#[derive(Clone)]
+struct IgnoredChildCallback(Rc<IgnoredChildFn>);
+
+impl std::fmt::Debug for IgnoredChildCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "IgnoredChildCallback(..)")
+    }
+}
+
+#[derive(Debug, Clone)]
+struct VariantChildKinds {
+    enum_name: &'static str,
+    variant: &'static str,
+    kinds: Vec<&'static str>,
+}
+
+/// Options for [`from_node_with_options`](crate::from_node_with_options).
+#[derive(Debug, Clone)]
+pub struct FromNodeOptions {
+    check_error: bool,
+    text_normalization: TextNormalization,
+    on_ignored_child: Option<IgnoredChildCallback>,
+    deny_unconsumed_children: bool,
+    element_limit: Option<usize>,
+    max_depth: Option<usize>,
+    reject_error_nodes: bool,
+    skip_error_nodes: bool,
+    lenient_tuple: bool,
+    variant_child_kinds: Vec<VariantChildKinds>,
+    kind_aliases: Vec<(&'static str, &'static str)>,
+    case_insensitive_kinds: bool,
+    unwrap_depth: usize,
+    trim_before_parse: bool,
+    radix_prefixes: bool,
+    allow_digit_separators: bool,
+    bool_literals: Vec<(&'static str, bool)>,
+    #[cfg(feature = "regex")]
+    kind_patterns: Vec<(&'static str, regex::Regex)>,
+}
+
+impl Default for FromNodeOptions {
+    fn default() -> Self {
+        Self {
+            check_error: true,
+            text_normalization: TextNormalization::None,
+            on_ignored_child: None,
+            deny_unconsumed_children: false,
+            element_limit: None,
+            max_depth: None,
+            reject_error_nodes: false,
+            skip_error_nodes: false,
+            lenient_tuple: false,
+            variant_child_kinds: Vec::new(),
+            kind_aliases: Vec::new(),
+            case_insensitive_kinds: false,
+            unwrap_depth: 0,
+            trim_before_parse: false,
+            radix_prefixes: false,
+            allow_digit_separators: false,
+            bool_literals: Vec::new(),
+            #[cfg(feature = "regex")]
+            kind_patterns: Vec::new(),
+        }
+    }
+}
+
+impl FromNodeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fail with [`DeserializeError::TreeSitterError`](crate::DeserializeError::TreeSitterError)
+    /// if the tree contains errors. Default: `true`.
+    pub fn check_error(mut self, check_error: bool) -> Self {
+        self.check_error = check_error;
+        self
+    }
+
+    /// Normalize node text before parsing `bool`/integers/floats, and node kinds before matching
+    /// enum variants. Default: [`TextNormalization::None`].
+    pub fn text_normalization(mut self, text_normalization: TextNormalization) -> Self {
+        self.text_normalization = text_normalization;
+        self
+    }
+
+    /// Call `callback` with the kind and the byte range of each named child of a struct node
+    /// that is not claimed by any field of the struct. Default: ignore them silently.
+    pub fn on_ignored_child<F: Fn(&str, Range<usize>) + 'static>(mut self, callback: F) -> Self {
+        self.on_ignored_child = Some(IgnoredChildCallback(Rc::new(callback)));
+        self
+    }
+
+    /// Fail with [`UnconsumedChildren`](crate::DeserializeError::UnconsumedChildren)
+    /// after deserialization if any named child of a deserialized node was not consumed. Default:
+    /// `false`.
+    ///
+    /// This reports the children that are not claimed by any field of a struct, and the children
+    /// of unit structs and unit variants. It is useful to find gaps in a mapping. Atoms consume
+    /// their children as a part of the text. Don't combine this with the options that ignore extra
+    /// children on purpose(e.g. [`Self::lenient_tuple`]): the children they ignore are not tracked.
+    pub fn deny_unconsumed_children(mut self, deny_unconsumed_children: bool) -> Self {
+        self.deny_unconsumed_children = deny_unconsumed_children;
+        self
+    }
+
+    /// Fail with [`DeserializeError::ErrorNode`](crate::DeserializeError::ErrorNode) if a node
+    /// being deserialized is an `ERROR` node. Unlike [`Self::check_error`], errors in the nodes
+    /// that are not deserialized are ignored. Default: `false`.
+    pub fn reject_error_nodes(mut self, reject_error_nodes: bool) -> Self {
+        self.reject_error_nodes = reject_error_nodes;
+        self
+    }
+
+    /// Ignore `ERROR` and `MISSING` nodes as if they were not in the tree, instead of failing
+    /// with [`DeserializeError::TreeSitterError`](crate::DeserializeError::TreeSitterError).
+    /// This gives a best-effort result for a source that tree-sitter recovered from.
+    /// Overrides [`Self::check_error`]. Default: `false`.
+    ///
+    /// Children are counted after they are ignored, e.g. `[1, 2 3]` is an array of 2 elements
+    /// for tuples and child length checks.
+    pub fn skip_error_nodes(mut self, skip_error_nodes: bool) -> Self {
+        self.skip_error_nodes = skip_error_nodes;
+        self
+    }
+
+    /// Let tuples take the first N named children and ignore the rest, instead of failing with
+    /// [`DeserializeError::ChildLength`](crate::DeserializeError::ChildLength). A trailing
+    /// sequence still takes all the remaining children. Default: `false`.
+    pub fn lenient_tuple(mut self, lenient_tuple: bool) -> Self {
+        self.lenient_tuple = lenient_tuple;
+        self
+    }
+
+    /// Fail with [`ElementLimitExceeded`](crate::DeserializeError::ElementLimitExceeded)
+    /// if the total number of sequence elements exceeds `limit`. Default: unlimited.
+    ///
+    /// This bounds the work for untrusted input.
+    pub fn element_limit(mut self, limit: usize) -> Self {
+        self.element_limit = Some(limit);
+        self
+    }
+
+    /// Fail with [`DepthLimitExceeded`](crate::DeserializeError::DepthLimitExceeded)
+    /// if nodes nested deeper than `limit` are deserialized. The root node is at depth 1.
+    /// Default: unlimited.
+    ///
+    /// This prevents stack overflow on deeply nested untrusted input.
+    pub fn max_depth(mut self, limit: usize) -> Self {
+        self.max_depth = Some(limit);
+        self
+    }
+
+    /// Select `variant` of the enum `enum_name` if the node has a named child of any of `kinds`.
+    ///
+    /// The variant is deserialized from the first such child. Variants are tried in the order of
+    /// configuration. If no configured variant matches, the variant is selected by the node's kind
+    /// as usual.
+    pub fn variant_child_kinds(
+        mut self,
+        enum_name: &'static str,
+        variant: &'static str,
+        kinds: &[&'static str],
+    ) -> Self {
+        self.variant_child_kinds.push(VariantChildKinds {
+            enum_name,
+            variant,
+            kinds: kinds.to_vec(),
+        });
+        self
+    }
+
+    /// Accept nodes of any of `kinds` where the type named `name` is expected.
+    ///
+    /// `name` is the type name seen by serde(i.e. the value of `#[serde(rename = "...")]`). This
+    /// is useful when the grammar renamed a node kind, to accept both the old and the new kinds.
+    pub fn kind_aliases(mut self, name: &'static str, kinds: &[&'static str]) -> Self {
+        self.kind_aliases
+            .extend(kinds.iter().map(|kind| (name, *kind)));
+        self
+    }
+
+    /// Compare node kinds with type names and enum variants ignoring ASCII case. Default: `false`.
+    pub fn case_insensitive_kinds(mut self, case_insensitive_kinds: bool) -> Self {
+        self.case_insensitive_kinds = case_insensitive_kinds;
+        self
+    }
+
+    /// Where a struct, a newtype struct or an enum is expected but the node's kind doesn't match,
+    /// descend into its single named child up to `depth` levels until the kind matches. Default:
+    /// `0`(don't descend).
+    ///
+    /// This skips wrapper nodes like `expression` that contain exactly one meaningful child. Each
+    /// node on the way must have exactly one named child.
+    pub fn unwrap_single_child(mut self, depth: usize) -> Self {
+        self.unwrap_depth = depth;
+        self
+    }
+
+    /// Trim surrounding whitespace of node text before parsing `bool`/integers/floats. Default:
+    /// `false`.
+    ///
+    /// Some grammars include surrounding whitespace in a token's range. Whitespace inside the
+    /// text is still an error.
+    pub fn trim_before_parse(mut self, trim_before_parse: bool) -> Self {
+        self.trim_before_parse = trim_before_parse;
+        self
+    }
+
+    /// Parse integers with `0x`/`0o`/`0b` prefix in hexadecimal/octal/binary. Default: `false`.
+    ///
+    /// This also enables [`Self::allow_digit_separators`] to accept e.g. `0xFF_FF`.
+    pub fn radix_prefixes(mut self, radix_prefixes: bool) -> Self {
+        self.radix_prefixes = radix_prefixes;
+        self
+    }
+
+    /// Ignore `_` digit separators when parsing integers/floats(e.g. `1_000_000`, `3.14_159`).
+    /// Default: `false`.
+    ///
+    /// Each `_` must be between two alphanumeric characters. Otherwise the text is parsed as
+    /// is and fails, e.g. `_1`, `1_`, `1__2` and `1._5` are errors.
+    pub fn allow_digit_separators(mut self, allow_digit_separators: bool) -> Self {
+        self.allow_digit_separators = allow_digit_separators;
+        self
+    }
+
+    /// Parse `truthy` as `true` and `falsy` as `false`, in addition to `true`/`false`(e.g.
+    /// `yes`/`no`, `on`/`off`).
+    ///
+    /// Literals are compared with the text after [`Self::text_normalization`].
+    pub fn bool_literals(mut self, truthy: &[&'static str], falsy: &[&'static str]) -> Self {
+        self.bool_literals
+            .extend(truthy.iter().map(|literal| (*literal, true)));
+        self.bool_literals
+            .extend(falsy.iter().map(|literal| (*literal, false)));
+        self
+    }
+
+    /// Accept nodes whose kind matches `pattern` where the type named `name` is expected.
+    ///
+    /// `name` is the type name seen by serde(i.e. the value of `#[serde(rename = "...")]`).
+    /// The pattern is matched against the whole kind.
+    #[cfg(feature = "regex")]
+    pub fn kind_pattern(mut self, name: &'static str, pattern: regex::Regex) -> Self {
+        // Anchored as a whole, since `find` prefers the first alternative: `a|ab` finds `a` in
+        // `ab`.
+        let anchored = regex::Regex::new(&format!("^(?:{})$", pattern.as_str()))
+            .expect("a valid pattern is valid when anchored");
+        self.kind_patterns.push((name, anchored));
+        self
+    }
+
+    #[cfg(feature = "tree-sitter")]
+    pub(crate) fn is_check_error(&self) -> bool {
+        self.check_error
+    }
+
+    pub(crate) fn is_deny_unconsumed_children(&self) -> bool {
+        self.deny_unconsumed_children
+    }
+
+    pub(crate) fn is_lenient_tuple(&self) -> bool {
+        self.lenient_tuple
+    }
+
+    pub(crate) fn is_reject_error_nodes(&self) -> bool {
+        self.reject_error_nodes
+    }
+
+    pub(crate) fn is_case_insensitive_kinds(&self) -> bool {
+        self.case_insensitive_kinds
+    }
+
+    #[cfg(feature = "tree-sitter")]
+    pub(crate) fn is_skip_error_nodes(&self) -> bool {
+        self.skip_error_nodes
+    }
+
+    pub(crate) fn is_radix_prefixes(&self) -> bool {
+        self.radix_prefixes
+    }
+
+    pub(crate) fn is_allow_digit_separators(&self) -> bool {
+        self.allow_digit_separators
+    }
+
+    /// Finds the value of a literal configured by [`Self::bool_literals`].
+    pub(crate) fn bool_literal(&self, text: &str) -> Option<bool> {
+        self.bool_literals
+            .iter()
+            .find(|(literal, _)| *literal == text)
+            .map(|(_, value)| *value)
+    }
+
+    pub(crate) fn get_unwrap_depth(&self) -> usize {
+        self.unwrap_depth
+    }
+
+    pub(crate) fn get_max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    pub(crate) fn get_element_limit(&self) -> Option<usize> {
+        self.element_limit
+    }
+
+    pub(crate) fn ignored_child_callback(&self) -> Option<&IgnoredChildFn> {
+        self.on_ignored_child.as_ref().map(|c| &*c.0)
+    }
+
+    /// Finds the variant configured by [`Self::variant_child_kinds`] and the child it matched.
+    pub(crate) fn variant_by_child_kind<'de, N: TsNode<'de>>(
+        &self,
+        enum_name: &str,
+        node: &N,
+    ) -> Option<(&'static str, N)> {
+        self.variant_child_kinds
+            .iter()
+            .filter(|v| v.enum_name == enum_name)
+            .find_map(|v| {
+                node.named_children()
+                    .find(|c| v.kinds.contains(&c.kind()))
+                    .map(|c| (v.variant, c))
+            })
+    }
+
+    pub(crate) fn normalize<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        self.text_normalization.apply(text)
+    }
+
+    /// Prepares node text for parsing a primitive.
+    pub(crate) fn normalize_for_parse<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.trim_before_parse {
+            self.normalize(text.trim())
+        } else {
+            self.normalize(text)
+        }
+    }
+
+    pub(crate) fn kind_matches(&self, name: &str, kind: &str) -> bool {
+        if name == kind || self.kind_aliases.contains(&(name, kind)) {
+            return true;
+        }
+        if self.case_insensitive_kinds && name.eq_ignore_ascii_case(kind) {
+            return true;
+        }
+        #[cfg(feature = "regex")]
+        if self
+            .kind_patterns
+            .iter()
+            .any(|(n, pattern)| *n == name && pattern.is_match(kind))
+        {
+            return true;
+        }
+        false
+    }
+}
@@ -1,26 +1,267 @@
+#[cfg(feature = "tree-sitter")]
+pub use tree_sitter::Point;
+
+/// Zero-based row and column of a position in the source.
+///
+/// This is `tree_sitter::Point` if the `tree-sitter` feature is enabled.
+#[cfg(not(feature = "tree-sitter"))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub row: usize,
+    pub column: usize,
+}
+
+#[cfg(not(feature = "tree-sitter"))]
+impl Point {
+    pub fn new(row: usize, column: usize) -> Self {
+        Point { row, column }
+    }
+}
+
+/// A syntax tree node that can be deserialized.
+///
+/// This is implemented for tree-sitter nodes by [`TsNodeImpl`]. Implement it for other tree
+/// representations and use [`from_ts_node`](crate::from_ts_node) to deserialize them.
+///
+/// Only named nodes are visible to the deserializer. Implementations must follow tree-sitter's
+/// semantics:
+///
+/// * `named_child`, `named_child_count` and `named_children` return the named children, in
+///   source order. Anonymous nodes(punctuation, keywords, etc.) are not included.
+/// * `child_count` and `children` return all the children including anonymous nodes, in source
+///   order.
+/// * `children_by_field_name` returns the named children assigned to the field, in source order.
+///   `field_names` returns the names of such fields, in order of their first appearance.
+/// * The iterators don't have to know their lengths. The deserializer takes the numbers of
+///   children from `named_child_count` and `child_count`, so they must agree with the iterators.
+/// * `kind` returns the grammar name of the node.
+/// * `src` returns the node's own text, not the whole source. The returned text must live as long
+///   as `'de` so that `&str` values can borrow it. If the source may not be valid UTF-8, override
+///   `src_bytes` and `try_src` too: the deserializer reads text through them, and `src` may return
+///   an empty string for the text that is not valid UTF-8. Callers that need to tell an empty node
+///   from an invalid one must use `try_src`.
+/// * `byte_range`, `start_position` and `end_position` return the node's location in the whole
+///   source.
+/// * `field_name` returns the name of the field that the parent assigned the node to. It returns
+///   `None` by default, for trees without links to parents.
+/// * `next_named_sibling` and `prev_named_sibling` return the adjacent named nodes under the same
+///   parent. They return `None` by default, for trees without links to siblings.
 pub trait TsNode<'de>: Clone + std::fmt::Debug
 where
     Self: Sized,
 {
     fn named_child(&self, index: usize) -> Option<Self>;
     fn named_child_count(&self) -> usize;
-    fn named_children(&self) -> impl ExactSizeIterator<Item = Self>;
-    fn children_by_field_name(&self, name: &str) -> impl ExactSizeIterator<Item = Self>;
+    fn named_children(&self) -> impl Iterator<Item = Self>;
+    fn child_count(&self) -> usize;
+    fn children(&self) -> impl Iterator<Item = Self>;
+    fn children_by_field_name(&self, name: &str) -> impl Iterator<Item = Self>;
+    /// Names of the fields that the node's children are assigned to, without duplicates.
+    fn field_names(&self) -> Vec<&'static str>;
     fn kind(&self) -> &'static str;
     fn src(&self) -> &'de str;
+    /// The node's own text as bytes, that may not be valid UTF-8.
+    fn src_bytes(&self) -> &'de [u8] {
+        self.src().as_bytes()
+    }
+    /// The node's own text, or an error if it is not valid UTF-8.
+    fn try_src(&self) -> Result<&'de str, std::str::Utf8Error> {
+        Ok(self.src())
+    }
+    fn byte_range(&self) -> std::ops::Range<usize>;
+    fn start_position(&self) -> Point;
+    fn end_position(&self) -> Point;
+    /// Identifier of the node, unique within the tree. IDs of nodes from different trees may
+    /// collide.
+    fn id(&self) -> usize;
+    /// True if the node is an `ERROR` node.
+    fn is_error(&self) -> bool;
+    fn field_name(&self) -> Option<&'static str> {
+        None
+    }
+    fn next_named_sibling(&self) -> Option<Self> {
+        None
+    }
+    fn prev_named_sibling(&self) -> Option<Self> {
+        None
+    }
+
+    /// Children assigned to fields with their field names, in one pass.
+    ///
+    /// The default implementation calls `children_by_field_name` for each field. Override this if
+    /// it can be done without scanning the children for each field.
+    fn field_children(&self) -> Vec<(&'static str, Self)> {
+        self.field_names()
+            .into_iter()
+            .flat_map(|f| self.children_by_field_name(f).map(move |c| (f, c)))
+            .collect()
+    }
+
+    /// First named child of the given kind.
+    fn named_child_by_kind(&self, kind: &str) -> Option<Self> {
+        self.named_children().find(|c| c.kind() == kind)
+    }
 }
 
+/// [`TsNode`] implementation for tree-sitter nodes.
+#[cfg(feature = "tree-sitter")]
 #[derive(Clone)]
 pub struct TsNodeImpl<'a, 'de> {
     node: tree_sitter::Node<'a>,
-    src: &'de str,
+    src: Source<'de>,
+    skip_error_nodes: bool,
+    /// Field name in the parent, if known when the node was reached from the parent.
+    field_name: Option<Option<&'static str>>,
+}
+
+/// Whole source of the tree, that is known to be valid UTF-8 or not.
+#[cfg(feature = "tree-sitter")]
+#[derive(Clone, Copy)]
+enum Source<'de> {
+    Str(&'de str),
+    Bytes(&'de [u8]),
 }
+
+#[cfg(feature = "tree-sitter")]
 impl<'a, 'de> TsNodeImpl<'a, 'de> {
     pub fn new(node: tree_sitter::Node<'a>, src: &'de str) -> Self {
-        Self { node, src }
+        Self {
+            node,
+            src: Source::Str(src),
+            skip_error_nodes: false,
+            field_name: None,
+        }
+    }
+
+    /// Same as `new`, but the source may not be valid UTF-8.
+    ///
+    /// The node's text is validated when it is read as text. [`TsNode::src`] returns an empty
+    /// string if the text is not valid UTF-8.
+    pub fn from_bytes(node: tree_sitter::Node<'a>, src: &'de [u8]) -> Self {
+        Self {
+            node,
+            src: Source::Bytes(src),
+            skip_error_nodes: false,
+            field_name: None,
+        }
+    }
+
+    /// Hide `ERROR` and `MISSING` nodes from the children of this node and its descendants.
+    pub fn skip_error_nodes(mut self, skip_error_nodes: bool) -> Self {
+        self.skip_error_nodes = skip_error_nodes;
+        self
+    }
+
+    fn wrap(&self, node: tree_sitter::Node<'a>) -> Self {
+        TsNodeImpl {
+            node,
+            src: self.src,
+            skip_error_nodes: self.skip_error_nodes,
+            field_name: None,
+        }
+    }
+
+    /// Same as `wrap`, for a child whose field name is known.
+    fn wrap_child(&self, node: tree_sitter::Node<'a>, field_name: Option<&'static str>) -> Self {
+        TsNodeImpl {
+            field_name: Some(field_name),
+            ..self.wrap(node)
+        }
+    }
+
+    /// Same as [`TsNode::named_children`], but the iterator doesn't borrow `self`.
+    pub(crate) fn into_named_children(self) -> Children<'a, 'de> {
+        Children::new(&self, ChildFilter::Named)
+    }
+
+    fn is_visible(&self, node: &tree_sitter::Node) -> bool {
+        !(self.skip_error_nodes && (node.is_error() || node.is_missing()))
+    }
+}
+
+/// Children of a [`TsNodeImpl`] to iterate.
+#[cfg(feature = "tree-sitter")]
+#[derive(Clone, Copy)]
+enum ChildFilter {
+    All,
+    Named,
+}
+
+/// Iterator over children of a [`TsNodeImpl`], that walks a cursor instead of collecting them.
+#[cfg(feature = "tree-sitter")]
+#[derive(Clone)]
+pub(crate) struct Children<'a, 'de> {
+    parent: TsNodeImpl<'a, 'de>,
+    cursor: tree_sitter::TreeCursor<'a>,
+    started: bool,
+    filter: ChildFilter,
+    remaining: usize,
+}
+
+#[cfg(feature = "tree-sitter")]
+impl<'a, 'de> Children<'a, 'de> {
+    fn new(parent: &TsNodeImpl<'a, 'de>, filter: ChildFilter) -> Self {
+        let mut children = Children {
+            parent: parent.clone(),
+            cursor: parent.node.walk(),
+            started: false,
+            filter,
+            remaining: usize::MAX,
+        };
+        // Counts are known without walking unless some children are hidden.
+        children.remaining = match filter {
+            ChildFilter::All if !parent.skip_error_nodes => parent.node.child_count(),
+            ChildFilter::Named if !parent.skip_error_nodes => parent.node.named_child_count(),
+            _ => children.clone().count(),
+        };
+        children
+    }
+
+    fn matches(&self) -> bool {
+        let node = self.cursor.node();
+        self.parent.is_visible(&node)
+            && match self.filter {
+                ChildFilter::All => true,
+                ChildFilter::Named => node.is_named(),
+            }
     }
 }
 
+#[cfg(feature = "tree-sitter")]
+impl<'a, 'de> Iterator for Children<'a, 'de> {
+    type Item = TsNodeImpl<'a, 'de>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        loop {
+            let moved = if self.started {
+                self.cursor.goto_next_sibling()
+            } else {
+                self.started = true;
+                self.cursor.goto_first_child()
+            };
+            if !moved {
+                self.remaining = 0;
+                return None;
+            }
+            if self.matches() {
+                self.remaining -= 1;
+                return Some(
+                    self.parent
+                        .wrap_child(self.cursor.node(), self.cursor.field_name()),
+                );
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+#[cfg(feature = "tree-sitter")]
 impl<'a, 'de> std::fmt::Debug for TsNodeImpl<'a, 'de> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("TsNodeImpl")
@@ -31,57 +272,203 @@ impl<'a, 'de> std::fmt::Debug for TsNodeImpl<'a, 'de> {
     }
 }
 
+#[cfg(feature = "tree-sitter")]
 impl<'a, 'de> TsNode<'de> for TsNodeImpl<'a, 'de> {
     fn named_child(&self, index: usize) -> Option<Self> {
-        self.node.named_child(index).map(|c| TsNodeImpl {
-            node: c,
-            src: self.src,
-        })
+        if self.skip_error_nodes {
+            return self.named_children().nth(index);
+        }
+        self.node.named_child(index).map(|c| self.wrap(c))
     }
 
     fn named_child_count(&self) -> usize {
+        if self.skip_error_nodes {
+            return self.named_children().count();
+        }
         self.node.named_child_count()
     }
 
-    fn named_children(&self) -> impl ExactSizeIterator<Item = Self> {
-        let mut cursor = self.node.walk();
-        let children = self.node.named_children(&mut cursor).collect::<Vec<_>>();
-        children.into_iter().map(|node| TsNodeImpl {
-            node,
-            src: self.src,
-        })
+    fn named_children(&self) -> impl Iterator<Item = Self> {
+        Children::new(self, ChildFilter::Named)
     }
 
-    fn children_by_field_name(&self, name: &str) -> impl ExactSizeIterator<Item = Self> {
+    fn child_count(&self) -> usize {
+        if self.skip_error_nodes {
+            return self.children().count();
+        }
+        self.node.child_count()
+    }
+
+    fn children(&self) -> impl Iterator<Item = Self> {
+        Children::new(self, ChildFilter::All)
+    }
+
+    fn children_by_field_name(&self, name: &str) -> impl Iterator<Item = Self> {
+        // Collected since the iterator borrows the cursor.
         let mut cursor = self.node.walk();
         self.node
             .children_by_field_name(name, &mut cursor)
+            .filter(|c| self.is_visible(c))
             .collect::<Vec<_>>()
             .into_iter()
-            .map(|node| TsNodeImpl {
-                node,
-                src: self.src,
-            })
+            .map(|node| self.wrap(node))
+    }
+
+    fn field_names(&self) -> Vec<&'static str> {
+        let mut cursor = self.node.walk();
+        let mut names = Vec::new();
+        if cursor.goto_first_child() {
+            loop {
+                if let Some(name) = cursor.field_name() {
+                    if self.is_visible(&cursor.node()) && !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+                if !cursor.goto_next_sibling() {
+                    break;
+                }
+            }
+        }
+        names
+    }
+
+    fn field_children(&self) -> Vec<(&'static str, Self)> {
+        let mut cursor = self.node.walk();
+        let mut children = Vec::new();
+        if cursor.goto_first_child() {
+            loop {
+                if let Some(name) = cursor.field_name() {
+                    let node = cursor.node();
+                    if self.is_visible(&node) {
+                        children.push((name, self.wrap_child(node, Some(name))));
+                    }
+                }
+                if !cursor.goto_next_sibling() {
+                    break;
+                }
+            }
+        }
+        children
     }
 
     fn kind(&self) -> &'static str {
         self.node.kind()
     }
 
+    /// The node's own text. If the source is not valid UTF-8([`Self::from_bytes`]), invalid text
+    /// is returned as an empty string: this is only for debugging, and the deserializer reads text
+    /// through [`TsNode::try_src`] to report the error.
     fn src(&self) -> &'de str {
-        &self.src[self.node.byte_range()]
+        self.try_src().unwrap_or_default()
+    }
+
+    fn src_bytes(&self) -> &'de [u8] {
+        match self.src {
+            Source::Str(src) => &src.as_bytes()[self.node.byte_range()],
+            Source::Bytes(src) => &src[self.node.byte_range()],
+        }
+    }
+
+    fn try_src(&self) -> Result<&'de str, std::str::Utf8Error> {
+        match self.src {
+            Source::Str(src) => Ok(&src[self.node.byte_range()]),
+            Source::Bytes(src) => std::str::from_utf8(&src[self.node.byte_range()]),
+        }
+    }
+
+    fn byte_range(&self) -> std::ops::Range<usize> {
+        self.node.byte_range()
+    }
+
+    fn start_position(&self) -> tree_sitter::Point {
+        self.node.start_position()
+    }
+
+    fn end_position(&self) -> tree_sitter::Point {
+        self.node.end_position()
+    }
+
+    fn id(&self) -> usize {
+        self.node.id()
+    }
+
+    fn is_error(&self) -> bool {
+        self.node.is_error()
+    }
+
+    fn field_name(&self) -> Option<&'static str> {
+        if let Some(field_name) = self.field_name {
+            return field_name;
+        }
+        let parent = self.node.parent()?;
+        let mut cursor = parent.walk();
+        if !cursor.goto_first_child() {
+            return None;
+        }
+        while cursor.node().id() != self.node.id() {
+            if !cursor.goto_next_sibling() {
+                return None;
+            }
+        }
+        cursor.field_name()
+    }
+
+    fn next_named_sibling(&self) -> Option<Self> {
+        std::iter::successors(self.node.next_named_sibling(), |n| n.next_named_sibling())
+            .find(|n| self.is_visible(n))
+            .map(|n| self.wrap(n))
+    }
+
+    fn prev_named_sibling(&self) -> Option<Self> {
+        std::iter::successors(self.node.prev_named_sibling(), |n| n.prev_named_sibling())
+            .find(|n| self.is_visible(n))
+            .map(|n| self.wrap(n))
     }
 }
 
-#[allow(dead_code)]
-pub fn show_node<'de, N: TsNode<'de>>(node: &N) {
-    fn show<'de, N: TsNode<'de>>(node: &N, indent: usize) {
-        let indent_string = " ".to_string().repeat(indent * 2);
-        print!("{indent_string}");
-        println!("- {}", node.kind());
-        for i in 0..node.named_child_count() {
-            show(&node.named_child(i).unwrap(), indent + 1);
+/// Text of the node, or an error at the node if it is not valid UTF-8.
+pub(crate) fn text<'de, N: TsNode<'de>>(node: &N) -> Result<&'de str, crate::DeserializeError> {
+    node.try_src()
+        .map_err(|e| crate::DeserializeError::Utf8Error(e).at(node.byte_range()))
+}
+
+/// Indented tree of the node and its named descendants, for debugging.
+///
+/// Each line shows the field name if any, the kind and the beginning of the text, e.g.
+/// `- key: string "\"a\""`.
+pub fn dump_node<'de, N: TsNode<'de>>(node: &N) -> String {
+    const SNIPPET_LEN: usize = 20;
+    fn dump<'de, N: TsNode<'de>>(
+        out: &mut String,
+        node: &N,
+        field_name: Option<&str>,
+        indent: usize,
+    ) {
+        use std::fmt::Write;
+        let src = String::from_utf8_lossy(node.src_bytes());
+        let mut snippet = src.chars().take(SNIPPET_LEN).collect::<String>();
+        if src.chars().nth(SNIPPET_LEN).is_some() {
+            snippet.push_str("...");
+        }
+        let field = field_name.map(|f| format!("{f}: ")).unwrap_or_default();
+        let prefix = "  ".repeat(indent);
+        let _ = writeln!(out, "{prefix}- {field}{} {snippet:?}", node.kind());
+        let fields = node
+            .field_children()
+            .into_iter()
+            .map(|(f, c)| (c.id(), f))
+            .collect::<std::collections::HashMap<_, _>>();
+        for child in node.named_children() {
+            dump(out, &child, fields.get(&child.id()).copied(), indent + 1);
         }
     }
-    show(node, 0);
+    let mut out = String::new();
+    dump(&mut out, node, None, 0);
+    out
+}
+
+/// Prints [`dump_node`] of the node.
+#[allow(dead_code)]
+pub fn show_node<'de, N: TsNode<'de>>(node: &N) {
+    print!("{}", dump_node(node));
 }
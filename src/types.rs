@@ -0,0 +1,1214 @@
+//! Wrapper types that are recognized by the deserializer.
+//!
+//! Each type calls `deserialize_newtype_struct` with a reserved name, and the deserializers in
+//! this crate dispatch on that name instead of matching it against the node kind.
+
+use std::cell::Cell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Range;
+
+use serde::{Deserialize, Deserializer};
+
+const PREFIX: &str = "$serde_tree_sitter::";
+pub(crate) const CHILD_KINDS: &str = "$serde_tree_sitter::ChildKinds";
+pub(crate) const NODE_KIND: &str = "$serde_tree_sitter::NodeKind";
+pub(crate) const NODE_ID: &str = "$serde_tree_sitter::NodeId";
+pub(crate) const RAW_TEXT: &str = "$serde_tree_sitter::RawText";
+pub(crate) const ALL_CHILDREN: &str = "$serde_tree_sitter::AllChildren";
+pub(crate) const TREE_VALUE: &str = "$serde_tree_sitter::TreeValue";
+pub(crate) const KIND_TAGGED: &str = "$serde_tree_sitter::KindTagged";
+pub(crate) const SPAN: &str = "$serde_tree_sitter::Span";
+pub(crate) const FLAG: &str = "$serde_tree_sitter::Flag";
+pub(crate) const MAYBE_KIND: &str = "$serde_tree_sitter::MaybeKind";
+pub(crate) const LOCATED: &str = "$serde_tree_sitter::Located";
+pub(crate) const POSITIONED: &str = "$serde_tree_sitter::Positioned";
+pub(crate) const POSITION: &str = "$serde_tree_sitter::Position";
+pub(crate) const BY_CHILD: &str = "$serde_tree_sitter::ByChild";
+pub(crate) const FIRST: &str = "$serde_tree_sitter::First";
+pub(crate) const LAST: &str = "$serde_tree_sitter::Last";
+pub(crate) const JOINED: &str = "$serde_tree_sitter::Joined";
+pub(crate) const NEXT_SIBLING: &str = "$serde_tree_sitter::NextSibling";
+pub(crate) const PRESENCE: &str = "$serde_tree_sitter::Presence";
+pub(crate) const CACHED: &str = "$serde_tree_sitter::Cached";
+pub(crate) const NODE_KEY: &str = "$serde_tree_sitter::NodeKey";
+
+/// Reserved struct field names that capture the struct node itself instead of its field.
+pub(crate) const SPAN_FIELD: &str = "$span";
+pub(crate) const TEXT_FIELD: &str = "$text";
+/// Reserved struct field name that takes the named children not claimed by other fields.
+pub(crate) const REST_FIELD: &str = "$rest";
+/// Variant name that matches nodes of any kind not matched by other variants.
+pub(crate) const OTHER_VARIANT: &str = "$other";
+/// Key of the node kind in the map given to [`KindTagged`] values.
+pub(crate) const KIND_FIELD: &str = "$kind";
+
+pub(crate) fn is_reserved_field(field: &str) -> bool {
+    field == SPAN_FIELD || field == TEXT_FIELD
+}
+
+pub(crate) fn is_reserved_name(name: &str) -> bool {
+    name.starts_with(PREFIX)
+}
+
+/// Kinds of the node's named children.
+///
+/// ```
+/// # #[cfg(feature = "tree-sitter")] {
+/// use serde_tree_sitter::ChildKinds;
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "document")]
+/// struct Document(Array);
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "array")]
+/// struct Array(ChildKinds);
+///
+/// let src = r#"[1, "a", null]"#;
+/// let document: Document =
+///     serde_tree_sitter::from_str(src, tree_sitter_json::language(), true).unwrap();
+/// assert_eq!(document.0 .0 .0, ["number", "string", "null"]);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ChildKinds(pub Vec<String>);
+
+impl<'de> Deserialize<'de> for ChildKinds {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor;
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = ChildKinds;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("kinds of named children")
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                Vec::deserialize(deserializer).map(ChildKinds)
+            }
+        }
+        deserializer.deserialize_newtype_struct(CHILD_KINDS, Visitor)
+    }
+}
+
+/// Helper for `#[serde(deserialize_with)]` that returns kinds of the node's named children.
+pub fn child_kinds<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    ChildKinds::deserialize(deserializer).map(|kinds| kinds.0)
+}
+
+/// Kind of the node, instead of its text.
+///
+/// ```
+/// # #[cfg(feature = "tree-sitter")] {
+/// use serde_tree_sitter::NodeKind;
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "document")]
+/// struct Document(Array);
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "array")]
+/// struct Array(Vec<NodeKind>);
+///
+/// let src = "[1, true, null]";
+/// let document: Document =
+///     serde_tree_sitter::from_str(src, tree_sitter_json::language(), true).unwrap();
+/// assert_eq!(
+///     document.0 .0,
+///     [NodeKind("number".into()), NodeKind("true".into()), NodeKind("null".into())]
+/// );
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct NodeKind(pub String);
+
+impl<'de> Deserialize<'de> for NodeKind {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor;
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = NodeKind;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("kind of a node")
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                String::deserialize(deserializer).map(NodeKind)
+            }
+        }
+        deserializer.deserialize_newtype_struct(NODE_KIND, Visitor)
+    }
+}
+
+/// Identifier of the node, from [`TsNode::id`](crate::TsNode::id).
+///
+/// Values deserialized from the same node have the same ID, so it can be used to find out
+/// duplicated references to a node. The ID is unique only within a single tree: don't compare IDs
+/// of nodes from different trees, including a tree and its re-parsed version.
+///
+/// ```
+/// # #[cfg(feature = "tree-sitter")] {
+/// use serde_tree_sitter::{NextSibling, NodeId};
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "document")]
+/// struct Document(Object);
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "object")]
+/// struct Object(Pair);
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "pair")]
+/// struct Pair {
+///     key: NextSibling<NodeId>,
+///     value: NodeId,
+/// }
+///
+/// let src = r#"{"a": 1}"#;
+/// let document: Document =
+///     serde_tree_sitter::from_str(src, tree_sitter_json::language(), true).unwrap();
+/// let pair = document.0 .0;
+/// assert_eq!(pair.key.0, pair.value);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct NodeId(pub usize);
+
+impl<'de> Deserialize<'de> for NodeId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor;
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = NodeId;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("ID of a node")
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                usize::deserialize(deserializer).map(NodeId)
+            }
+        }
+        deserializer.deserialize_newtype_struct(NODE_ID, Visitor)
+    }
+}
+
+/// Source text of the node as is, even if the node is a struct.
+///
+/// Unlike `&str`, the text is not normalized. Use with the `$text` field to keep the whole text of
+/// a struct node along with its fields:
+///
+/// ```
+/// # #[cfg(feature = "tree-sitter")] {
+/// use serde_tree_sitter::RawText;
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "document")]
+/// struct Document<'a>(#[serde(borrow)] Object<'a>);
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "object")]
+/// struct Object<'a>(#[serde(borrow)] Pair<'a>);
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "pair")]
+/// struct Pair<'a> {
+///     key: &'a str,
+///     value: u32,
+///     #[serde(rename = "$text", borrow)]
+///     raw: RawText<'a>,
+/// }
+///
+/// let src = r#"{"a":  1}"#;
+/// let document: Document =
+///     serde_tree_sitter::from_str(src, tree_sitter_json::language(), true).unwrap();
+/// assert_eq!(
+///     document.0 .0,
+///     Pair {
+///         key: r#""a""#,
+///         value: 1,
+///         raw: RawText(r#""a":  1"#),
+///     }
+/// );
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct RawText<'a>(pub &'a str);
+
+impl<'de: 'a, 'a> Deserialize<'de> for RawText<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor<'a>(PhantomData<&'a ()>);
+        impl<'de: 'a, 'a> serde::de::Visitor<'de> for Visitor<'a> {
+            type Value = RawText<'a>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("source text of a node")
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                <&'de str>::deserialize(deserializer).map(RawText)
+            }
+        }
+        deserializer.deserialize_newtype_struct(RAW_TEXT, Visitor(PhantomData))
+    }
+}
+
+/// Values of all the node's children, including anonymous nodes(keywords, operators, etc.).
+///
+/// An anonymous node's kind is its text, so it can be matched by a unit variant:
+///
+/// ```
+/// # #[cfg(feature = "tree-sitter")] {
+/// use serde_tree_sitter::AllChildren;
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// enum Token {
+///     #[serde(rename = "[")]
+///     Open,
+///     #[serde(rename = "]")]
+///     Close,
+///     #[serde(rename = ",")]
+///     Comma,
+///     #[serde(rename = "number")]
+///     Number(u32),
+/// }
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "document")]
+/// struct Document(Array);
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "array")]
+/// struct Array(AllChildren<Token>);
+///
+/// let src = "[1, 2]";
+/// let document: Document =
+///     serde_tree_sitter::from_str(src, tree_sitter_json::language(), true).unwrap();
+/// assert_eq!(
+///     document.0 .0 .0,
+///     [Token::Open, Token::Number(1), Token::Comma, Token::Number(2), Token::Close]
+/// );
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct AllChildren<T>(pub Vec<T>);
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for AllChildren<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor<T>(PhantomData<T>);
+        impl<'de, T: Deserialize<'de>> serde::de::Visitor<'de> for Visitor<T> {
+            type Value = AllChildren<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("all children of a node")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut values = Vec::new();
+                while let Some(value) = seq.next_element()? {
+                    values.push(value);
+                }
+                Ok(AllChildren(values))
+            }
+        }
+        deserializer.deserialize_newtype_struct(ALL_CHILDREN, Visitor(PhantomData))
+    }
+}
+
+/// Any node, with its kind, text and named children.
+///
+/// This is an owned, self-describing representation of a tree, like `serde_json::Value`. Use it
+/// to process a tree without knowing its grammar.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct TreeValue {
+    pub kind: String,
+    pub text: String,
+    /// Named children with their field names, in source order.
+    pub children: Vec<(Option<String>, TreeValue)>,
+}
+
+impl<'de> Deserialize<'de> for TreeValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor;
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = TreeValue;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("kind, text, field names and children of a node")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let kind: String = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let text: String = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(1, &self))?;
+                let fields: Vec<String> = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(2, &self))?;
+                let children: Vec<TreeValue> = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(3, &self))?;
+                // Children not assigned to any field have empty field names.
+                let children = fields
+                    .into_iter()
+                    .map(|f| Some(f).filter(|f| !f.is_empty()))
+                    .zip(children)
+                    .collect();
+                Ok(TreeValue {
+                    kind,
+                    text,
+                    children,
+                })
+            }
+        }
+        deserializer.deserialize_newtype_struct(TREE_VALUE, Visitor)
+    }
+}
+
+/// Value of an internally tagged enum(`#[serde(tag = "$kind")]`) whose tag is the node's kind.
+///
+/// The enum is deserialized from a map of the node's kind as `$kind`, the node's text as
+/// `$text`, and its fields. Like `#[serde(flatten)]`, the variant's contents are buffered as
+/// text: use string-like types for the leaves.
+///
+/// ```
+/// # #[cfg(feature = "tree-sitter")] {
+/// use serde_tree_sitter::KindTagged;
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(tag = "$kind", rename_all = "snake_case")]
+/// enum Value {
+///     Null,
+///     Number {
+///         #[serde(rename = "$text")]
+///         text: String,
+///     },
+/// }
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "document")]
+/// struct Document(Array);
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "array")]
+/// struct Array(Vec<KindTagged<Value>>);
+///
+/// let src = "[null, 1]";
+/// let document: Document =
+///     serde_tree_sitter::from_str(src, tree_sitter_json::language(), true).unwrap();
+/// assert_eq!(
+///     document.0 .0,
+///     [KindTagged(Value::Null), KindTagged(Value::Number { text: "1".into() })]
+/// );
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct KindTagged<T>(pub T);
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for KindTagged<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor<T>(PhantomData<T>);
+        impl<'de, T: Deserialize<'de>> serde::de::Visitor<'de> for Visitor<T> {
+            type Value = KindTagged<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a node tagged with its kind")
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                T::deserialize(deserializer).map(KindTagged)
+            }
+        }
+        deserializer.deserialize_newtype_struct(KIND_TAGGED, Visitor(PhantomData))
+    }
+}
+
+/// `Some(T)` if the node's kind matches `T`, `None` otherwise.
+///
+/// The expected kind is the name of a struct(`#[serde(rename = "...")]`) or any variant of an
+/// enum. For other types, the node always matches. Unlike `Option<T>`, the node's children are
+/// not counted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct MaybeKind<T>(pub Option<T>);
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for MaybeKind<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor<T> {
+            expected: ExpectedKind,
+            _p: PhantomData<T>,
+        }
+        impl<'de, T: Deserialize<'de>> serde::de::Visitor<'de> for Visitor<T> {
+            type Value = MaybeKind<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a node and its kind")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let matched = seq
+                    .next_element_seed(KindMatchSeed(self.expected))?
+                    .unwrap_or(false);
+                if !matched {
+                    return Ok(MaybeKind(None));
+                }
+                seq.next_element().map(MaybeKind)
+            }
+        }
+        deserializer.deserialize_newtype_struct(
+            MAYBE_KIND,
+            Visitor {
+                expected: ExpectedKind::of::<T>(),
+                _p: PhantomData,
+            },
+        )
+    }
+}
+
+/// Kind(s) that a type expects, detected by [`KindProbe`].
+#[derive(Debug, Clone, Copy)]
+enum ExpectedKind {
+    Any,
+    Name(&'static str),
+    Variants(&'static [&'static str]),
+}
+
+impl ExpectedKind {
+    fn of<'de, T: Deserialize<'de>>() -> Self {
+        let probe = KindProbe(Cell::new(ExpectedKind::Any));
+        let _ = T::deserialize(&probe);
+        probe.0.get()
+    }
+}
+
+/// Deserializer that records the first name requested by a `Deserialize` impl and fails.
+struct KindProbe(Cell<ExpectedKind>);
+
+impl KindProbe {
+    fn record<T>(&self, expected: ExpectedKind) -> Result<T, crate::DeserializeError> {
+        self.0.set(expected);
+        Err(crate::DeserializeError::Custom("kind probe".into()))
+    }
+    fn record_name<T>(&self, name: &'static str) -> Result<T, crate::DeserializeError> {
+        if is_reserved_name(name) {
+            self.record(ExpectedKind::Any)
+        } else {
+            self.record(ExpectedKind::Name(name))
+        }
+    }
+}
+
+impl<'de> Deserializer<'de> for &KindProbe {
+    type Error = crate::DeserializeError;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.record(ExpectedKind::Any)
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.record_name(name)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.record_name(name)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        _len: usize,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.record_name(name)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.record_name(name)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.record(ExpectedKind::Variants(variants))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit seq tuple map identifier ignored_any
+    }
+}
+
+/// Checks the node's kind against [`ExpectedKind`] using the deserializer's own kind check.
+struct KindMatchSeed(ExpectedKind);
+
+impl<'de> serde::de::DeserializeSeed<'de> for KindMatchSeed {
+    type Value = bool;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match self.0 {
+            ExpectedKind::Any => Ok(true),
+            ExpectedKind::Name(name) => Ok(deserializer
+                .deserialize_unit_struct(name, serde::de::IgnoredAny)
+                .is_ok()),
+            ExpectedKind::Variants(variants) => {
+                let kind = String::deserialize(KindDeserializer(deserializer))?;
+                Ok(variants.contains(&kind.as_str()) || variants.contains(&OTHER_VARIANT))
+            }
+        }
+    }
+}
+
+/// Reads the node kind via `deserialize_identifier`.
+struct KindDeserializer<D>(D);
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for KindDeserializer<D> {
+    type Error = D::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.0.deserialize_identifier(visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Value deserialized from the node's single named child.
+///
+/// This is useful for enums whose unit variants correspond to keyword children:
+///
+/// ```
+/// # #[cfg(feature = "tree-sitter")] {
+/// use serde_tree_sitter::ByChild;
+///
+/// // document: $ => $._value, where _value includes $.true, $.false and $.null
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename_all = "snake_case")]
+/// enum Literal {
+///     True,
+///     False,
+///     Null,
+/// }
+///
+/// let document: ByChild<Literal> =
+///     serde_tree_sitter::from_str("null", tree_sitter_json::language(), true).unwrap();
+/// assert_eq!(document, ByChild(Literal::Null));
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ByChild<T>(pub T);
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for ByChild<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor<T>(PhantomData<T>);
+        impl<'de, T: Deserialize<'de>> serde::de::Visitor<'de> for Visitor<T> {
+            type Value = ByChild<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a node with single named child")
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                T::deserialize(deserializer).map(ByChild)
+            }
+        }
+        deserializer.deserialize_newtype_struct(BY_CHILD, Visitor(PhantomData))
+    }
+}
+
+/// Value of the first node of a struct field, that may have multiple nodes.
+///
+/// A field of other non-sequence types requires exact one node. Use this to take the first one
+/// instead, e.g. `declarator: First<Declarator>` for a declaration that declares multiple
+/// variables. Outside of a struct field, this is the same as `T`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct First<T>(pub T);
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for First<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor<T>(PhantomData<T>);
+        impl<'de, T: Deserialize<'de>> serde::de::Visitor<'de> for Visitor<T> {
+            type Value = First<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("the first node of a field")
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                T::deserialize(deserializer).map(First)
+            }
+        }
+        deserializer.deserialize_newtype_struct(FIRST, Visitor(PhantomData))
+    }
+}
+
+/// Value of the last node of a struct field, that may have multiple nodes.
+///
+/// Same as [`First`], but takes the last one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Last<T>(pub T);
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Last<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor<T>(PhantomData<T>);
+        impl<'de, T: Deserialize<'de>> serde::de::Visitor<'de> for Visitor<T> {
+            type Value = Last<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("the last node of a field")
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                T::deserialize(deserializer).map(Last)
+            }
+        }
+        deserializer.deserialize_newtype_struct(LAST, Visitor(PhantomData))
+    }
+}
+
+/// Value of the named sibling after the node.
+///
+/// This is useful for a node whose meaning depends on its neighbor, like a label followed by its
+/// value. As a struct field, the field must have exact one node. Fails with
+/// [`DeserializeError::NoNextSibling`](crate::DeserializeError::NoNextSibling) if there is no
+/// such sibling.
+///
+/// ```
+/// # #[cfg(feature = "tree-sitter")] {
+/// use serde_tree_sitter::NextSibling;
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "document")]
+/// struct Document(Object);
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "object")]
+/// struct Object(Pair);
+///
+/// // The value of the node after `key`, i.e. `value`
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "pair")]
+/// struct Pair {
+///     key: NextSibling<u32>,
+/// }
+///
+/// let src = r#"{"a": 1}"#;
+/// let document: Document =
+///     serde_tree_sitter::from_str(src, tree_sitter_json::language(), true).unwrap();
+/// assert_eq!(document.0 .0.key, NextSibling(1));
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct NextSibling<T>(pub T);
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for NextSibling<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor<T>(PhantomData<T>);
+        impl<'de, T: Deserialize<'de>> serde::de::Visitor<'de> for Visitor<T> {
+            type Value = NextSibling<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("the next named sibling of a node")
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                T::deserialize(deserializer).map(NextSibling)
+            }
+        }
+        deserializer.deserialize_newtype_struct(NEXT_SIBLING, Visitor(PhantomData))
+    }
+}
+
+/// Text of all nodes of a struct field, concatenated.
+///
+/// This is useful for a logical string split into multiple nodes of the same field, e.g.
+/// `part: Joined` for the parts of a concatenated string literal. Outside of a struct field, this
+/// is the text of the node.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Joined(pub String);
+
+impl<'de> Deserialize<'de> for Joined {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor;
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = Joined;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("text of nodes")
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                String::deserialize(deserializer).map(Joined)
+            }
+        }
+        deserializer.deserialize_newtype_struct(JOINED, Visitor)
+    }
+}
+
+/// Presence of a node.
+///
+/// In a struct field, `Flag(true)` means the field has exact one node and `Flag(false)` means
+/// the field is empty. Unlike `bool`, the node's text is not parsed. This is useful for keyword
+/// flags like `field('async', optional('async'))`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Flag(pub bool);
+
+impl<'de> Deserialize<'de> for Flag {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor;
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = Flag;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("presence of a node")
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                bool::deserialize(deserializer).map(Flag)
+            }
+        }
+        deserializer.deserialize_newtype_struct(FLAG, Visitor)
+    }
+}
+
+/// A struct field value with whether the field was present in the source.
+///
+/// If the field is empty, `value` is `T::default()` and `present` is `false`. Otherwise the field
+/// is deserialized as `T`. Outside of struct fields, `present` is always `true`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Presence<T> {
+    pub value: T,
+    pub present: bool,
+}
+
+impl<'de, T: Deserialize<'de> + Default> Deserialize<'de> for Presence<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor<T>(PhantomData<T>);
+        impl<'de, T: Deserialize<'de> + Default> serde::de::Visitor<'de> for Visitor<T> {
+            type Value = Presence<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("presence of a field and its value")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let present: bool = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                if !present {
+                    return Ok(Presence {
+                        value: T::default(),
+                        present,
+                    });
+                }
+                let value = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(1, &self))?;
+                Ok(Presence { value, present })
+            }
+        }
+        deserializer.deserialize_newtype_struct(PRESENCE, Visitor(PhantomData))
+    }
+}
+
+/// Byte range of a node in the source.
+///
+/// Deserializing a node into `Span` captures the node's byte range instead of its content.
+/// A struct field renamed to `$span` captures the span of the struct's own node:
+///
+/// ```
+/// # #[cfg(feature = "tree-sitter")] {
+/// use serde_tree_sitter::Span;
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "document")]
+/// struct Document(Object);
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "object")]
+/// struct Object(Pair);
+///
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// #[serde(rename = "pair")]
+/// struct Pair {
+///     #[serde(rename = "$span")]
+///     span: Span,
+///     key: Span,
+/// }
+///
+/// let src = r#"{"a": 1}"#;
+/// let document: Document =
+///     serde_tree_sitter::from_str(src, tree_sitter_json::language(), true).unwrap();
+/// assert_eq!(document.0 .0.span, Span::from(1..7));
+/// assert_eq!(document.0 .0.key, Span::from(1..4));
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+impl From<Range<usize>> for Span {
+    fn from(range: Range<usize>) -> Self {
+        Span {
+            start: range.start,
+            end: range.end,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Span {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor;
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = Span;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("byte range of a node")
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                let (start, end) = <(usize, usize)>::deserialize(deserializer)?;
+                Ok(Span { start, end })
+            }
+        }
+        deserializer.deserialize_newtype_struct(SPAN, Visitor)
+    }
+}
+
+/// A value with the [`Span`] of the node it was deserialized from.
+///
+/// In a struct field, the field must have exact one node, as for `T` itself.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Located<T> {
+    pub value: T,
+    pub span: Span,
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Located<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor<T>(PhantomData<T>);
+        impl<'de, T: Deserialize<'de>> serde::de::Visitor<'de> for Visitor<T> {
+            type Value = Located<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a node and its span")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let span = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let value = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(1, &self))?;
+                Ok(Located { value, span })
+            }
+        }
+        deserializer.deserialize_newtype_struct(LOCATED, Visitor(PhantomData))
+    }
+}
+
+/// A value with the byte range of the node it was deserialized from.
+///
+/// Same as [`Located`], but holds the range as `Range<usize>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Spanned<T> {
+    pub value: T,
+    pub range: Range<usize>,
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Spanned<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let Located { value, span } = Located::deserialize(deserializer)?;
+        Ok(Spanned {
+            value,
+            range: span.range(),
+        })
+    }
+}
+
+/// A value with the start and end positions(row and column) of the node it was deserialized from.
+///
+/// Rows and columns are zero-based, and columns are counted in bytes as in tree-sitter.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Positioned<T> {
+    pub value: T,
+    pub start: crate::Point,
+    pub end: crate::Point,
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Positioned<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor<T>(PhantomData<T>);
+        impl<'de, T: Deserialize<'de>> serde::de::Visitor<'de> for Visitor<T> {
+            type Value = Positioned<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a node and its position")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let Position { start, end } = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(0, &self))?;
+                let value = seq
+                    .next_element()?
+                    .ok_or_else(|| serde::de::Error::invalid_length(1, &self))?;
+                Ok(Positioned { value, start, end })
+            }
+        }
+        deserializer.deserialize_newtype_struct(POSITIONED, Visitor(PhantomData))
+    }
+}
+
+/// Start and end positions of a node, used by [`Positioned`].
+struct Position {
+    start: crate::Point,
+    end: crate::Point,
+}
+
+impl<'de> Deserialize<'de> for Position {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor;
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = Position;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("position of a node")
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                let (start_row, start_column, end_row, end_column) =
+                    <(usize, usize, usize, usize)>::deserialize(deserializer)?;
+                Ok(Position {
+                    start: crate::Point::new(start_row, start_column),
+                    end: crate::Point::new(end_row, end_column),
+                })
+            }
+        }
+        deserializer.deserialize_newtype_struct(POSITION, Visitor)
+    }
+}
+
+/// Values that know which part of the source they were deserialized from.
+///
+/// Implement this for types that capture their [`Span`](e.g. via a `$span` field) to use
+/// [`reassemble`].
+pub trait SourceSpanned {
+    fn span(&self) -> Span;
+}
+
+impl SourceSpanned for Span {
+    fn span(&self) -> Span {
+        *self
+    }
+}
+
+impl<T> SourceSpanned for Located<T> {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl<T> SourceSpanned for Spanned<T> {
+    fn span(&self) -> Span {
+        self.range.clone().into()
+    }
+}
+
+impl<T: SourceSpanned + ?Sized> SourceSpanned for &T {
+    fn span(&self) -> Span {
+        (**self).span()
+    }
+}
+
+impl<T: SourceSpanned + ?Sized> SourceSpanned for Box<T> {
+    fn span(&self) -> Span {
+        (**self).span()
+    }
+}
+
+/// Returns the source text `value` was deserialized from.
+///
+/// `root_src` must be the source passed to [`from_tree`](crate::from_tree) or
+/// [`from_node`](crate::from_node). Since spans refer to the original source, the result includes
+/// everything between the start and the end of the value(comments, whitespace, etc.).
+///
+/// # Panics
+///
+/// Panics if the span is out of `root_src`.
+pub fn reassemble<T: SourceSpanned + ?Sized>(root_src: &str, value: &T) -> String {
+    root_src[value.span().range()].to_owned()
+}